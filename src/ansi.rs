@@ -0,0 +1,161 @@
+//! # ANSI Escape Sequences
+//!
+//! Removes escape sequences from text (see [`Styled`](crate::Styled)) and
+//! measures the width of the visible text in terminal columns.
+
+use std::borrow::Cow;
+use unicode_width::UnicodeWidthStr;
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+
+/// Iterator over the visible text between escape sequences.
+///
+/// Recognizes CSI (`ESC [ ... final`), OSC (`ESC ] ... BEL` or `ESC ] ... ESC \`)
+/// and two-character escapes (e.g. `ESC c`). An incomplete sequence at the
+/// end of the text is removed.
+struct VisibleText<'a> {
+    rest: &'a str,
+}
+
+impl<'a> VisibleText<'a> {
+    fn new(text: &'a str) -> Self {
+        VisibleText { rest: text }
+    }
+
+    /// Returns the text after the escape sequence at the start of `rest`.
+    fn skip_escape(rest: &str) -> &str {
+        let body = &rest[ESC.len_utf8()..];
+
+        match body.chars().next() {
+            // CSI: parameter and intermediate bytes, then a final byte (0x40 to 0x7E).
+            Some('[') => {
+                let params = &body[1..];
+                match params.find(|c| ('\x40'..='\x7e').contains(&c)) {
+                    Some(i) => &params[i + 1..],
+                    None => "",
+                }
+            }
+            // OSC: terminated by BEL or by ST (ESC \).
+            Some(']') => {
+                let params = &body[1..];
+                match params.find([BEL, ESC]) {
+                    Some(i) if params[i..].starts_with("\x1b\\") => &params[i + 2..],
+                    Some(i) if params[i..].starts_with(BEL) => &params[i + 1..],
+                    // Another escape sequence ends the OSC.
+                    Some(i) => &params[i..],
+                    None => "",
+                }
+            }
+            Some(c) => &body[c.len_utf8()..],
+            None => "",
+        }
+    }
+}
+
+impl<'a> Iterator for VisibleText<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        while !self.rest.is_empty() {
+            match self.rest.find(ESC) {
+                Some(0) => self.rest = Self::skip_escape(self.rest),
+                Some(index) => {
+                    let (text, rest) = self.rest.split_at(index);
+                    self.rest = rest;
+                    return Some(text);
+                }
+                None => return Some(std::mem::take(&mut self.rest)),
+            }
+        }
+        None
+    }
+}
+
+/**
+Removes ANSI escape sequences (colors, cursor movements, hyperlinks) from `text`.
+
+Returns the text unchanged (borrowed) if it has no escape sequences.
+
+Example:
+```
+    use claudiofsr_lib::{Colors, strip_ansi};
+
+    let colored = format!("{} {}", "Total:".bold().enabled(true), "-12,50".red().enabled(true));
+    assert_eq!(strip_ansi(&colored), "Total: -12,50");
+
+    // OSC 8 hyperlink.
+    let link = "\x1b]8;;https://www.gov.br\x1b\\gov.br\x1b]8;;\x1b\\";
+    assert_eq!(strip_ansi(link), "gov.br");
+```
+*/
+pub fn strip_ansi(text: &str) -> Cow<'_, str> {
+    if !text.contains(ESC) {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(VisibleText::new(text).collect())
+}
+
+/**
+Returns the width of `text` in terminal columns, ignoring ANSI escape sequences.
+
+East Asian wide characters (and most emoji) count as 2 columns,
+combining marks and zero-width characters as 0.
+
+Example:
+```
+    use claudiofsr_lib::{Colors, display_width};
+
+    assert_eq!(display_width("Cláudio"), 7);
+    assert_eq!(display_width("おはよう"), 8);
+    assert_eq!(display_width("e\u{301}"), 1); // e + combining acute accent
+    assert_eq!(display_width(&"🦀 ok".green().enabled(true).to_string()), 5);
+```
+*/
+pub fn display_width(text: &str) -> usize {
+    VisibleText::new(text).map(UnicodeWidthStr::width).sum()
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output ansi_tests
+
+#[cfg(test)]
+mod ansi_tests {
+    use super::*;
+
+    #[test]
+    fn strip_sequences() {
+        let cases = [
+            ("", ""),
+            ("plain", "plain"),
+            ("\x1b[0m", ""),
+            ("\x1b[1;38;2;255;0;0mred\x1b[0m!", "red!"),
+            ("a\x1b[2Kb\x1b[1Gc", "abc"),
+            ("\x1bcreset", "reset"),
+            ("\x1b]0;title\x07text", "text"),
+            ("\x1b]8;;url\x1b\\link\x1b]8;;\x1b\\", "link"),
+            ("ünï\x1b[4mcödé\x1b[24m", "ünïcödé"),
+            ("incomplete\x1b[31", "incomplete"),
+            ("incomplete\x1b]8;;url", "incomplete"),
+            ("trailing\x1b", "trailing"),
+        ];
+
+        for (text, expected) in cases {
+            assert_eq!(strip_ansi(text), expected, "text: {text:?}");
+        }
+
+        assert!(matches!(strip_ansi("no escapes"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn width_of_wide_and_combining_characters() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("Bom dia おはよう!"), 17);
+        assert_eq!(display_width("\x1b[31m日本\x1b[0m語"), 6);
+        assert_eq!(display_width("a\u{0300}\u{0301}"), 1);
+        assert_eq!(display_width("\u{200B}"), 0);
+    }
+}
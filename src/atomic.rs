@@ -0,0 +1,275 @@
+//! # Atomic File Writes
+//!
+//! Writes to a temporary file in the same directory as the target,
+//! then fsyncs and renames it over the target, so readers never see
+//! a truncated output.
+
+use crate::rand;
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/**
+A file writer that replaces its target atomically on [`AtomicFile::commit`].
+
+Data is written to a hidden temporary file in the target directory
+(`.<file name>.<random>.tmp`). On commit the temporary file is flushed,
+synced to disk and renamed over the target.
+
+An existing target keeps its permissions. If the target is a symbolic
+link, the file it points to is replaced and the link is kept.
+
+If the `AtomicFile` is dropped without being committed (for example,
+after an error), the temporary file is removed and the target is left untouched.
+
+Example:
+```
+    use claudiofsr_lib::AtomicFile;
+    use std::io::Write;
+
+    let path = "/tmp/atomic_example.txt";
+
+    let mut file = AtomicFile::create(path).unwrap();
+    writeln!(file, "first line").unwrap();
+
+    // Nothing is visible at the target until commit.
+    assert!(!std::path::Path::new(path).exists());
+
+    file.commit().unwrap();
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "first line\n");
+
+    std::fs::remove_file(path).unwrap();
+```
+*/
+pub struct AtomicFile {
+    target: PathBuf,
+    temp: PathBuf,
+    /// `None` after commit.
+    writer: Option<BufWriter<File>>,
+}
+
+impl AtomicFile {
+    /// Creates the temporary file for the target `path`.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut target = path.as_ref().to_path_buf();
+
+        // Replace the file a symlink points to, not the link
+        // (a dangling link is replaced).
+        if fs::symlink_metadata(&target).is_ok_and(|metadata| metadata.is_symlink())
+            && let Ok(resolved) = fs::canonicalize(&target)
+        {
+            target = resolved;
+        }
+
+        let file_name = target.file_name().ok_or_else(|| {
+            let msg = format!("invalid file path: {target:?}");
+            io::Error::new(io::ErrorKind::InvalidInput, msg)
+        })?;
+
+        loop {
+            let mut temp_name = OsString::from(".");
+            temp_name.push(file_name);
+            temp_name.push(format!(".{:016x}.tmp", rand()));
+            let temp = target.with_file_name(temp_name);
+
+            match OpenOptions::new().write(true).create_new(true).open(&temp) {
+                Ok(file) => {
+                    return Ok(AtomicFile {
+                        target,
+                        temp,
+                        writer: Some(BufWriter::new(file)),
+                    });
+                }
+                // Name collision: try another random suffix.
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Returns the target path (resolved, if `path` was a symbolic link).
+    pub fn path(&self) -> &Path {
+        &self.target
+    }
+
+    /// Returns the temporary file path.
+    pub fn temp_path(&self) -> &Path {
+        &self.temp
+    }
+
+    /// Flushes, syncs and renames the temporary file over the target,
+    /// with the permissions of the target if it exists.
+    ///
+    /// On error the temporary file is removed and the target is left untouched.
+    pub fn commit(mut self) -> io::Result<()> {
+        let Some(writer) = self.writer.take() else {
+            return Ok(());
+        };
+
+        let replace = || {
+            let file = writer
+                .into_inner()
+                .map_err(io::IntoInnerError::into_error)?;
+            if let Ok(metadata) = fs::metadata(&self.target) {
+                file.set_permissions(metadata.permissions())?;
+            }
+            file.sync_all()?;
+            drop(file);
+            fs::rename(&self.temp, &self.target)
+        };
+
+        if let Err(error) = replace() {
+            let _ = fs::remove_file(&self.temp);
+            return Err(error);
+        }
+
+        sync_parent_dir(&self.target)
+    }
+
+    fn writer(&mut self) -> io::Result<&mut BufWriter<File>> {
+        self.writer
+            .as_mut()
+            .ok_or_else(|| io::Error::other("AtomicFile already committed"))
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer()?.write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.writer()?.write_all(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer()?.flush()
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        if self.writer.take().is_some() {
+            let _ = fs::remove_file(&self.temp);
+        }
+    }
+}
+
+/// Syncs the directory entry so the rename survives a crash.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => File::open(dir)?.sync_all(),
+        _ => File::open(".")?.sync_all(),
+    }
+}
+
+/// Directories cannot be opened as files on this platform.
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output atomic_tests
+
+#[cfg(test)]
+mod atomic_tests {
+    use super::*;
+
+    #[test]
+    fn commit_replaces_target() -> io::Result<()> {
+        let path = "/tmp/atomic_replace.txt";
+        fs::write(path, "old content\n")?;
+
+        let mut file = AtomicFile::create(path)?;
+        let temp = file.temp_path().to_path_buf();
+        file.write_all(b"new content\n")?;
+
+        // The target keeps the old content until commit.
+        assert_eq!(fs::read_to_string(path)?, "old content\n");
+        assert!(temp.exists());
+
+        file.commit()?;
+
+        assert_eq!(fs::read_to_string(path)?, "new content\n");
+        assert!(!temp.exists());
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn drop_without_commit_keeps_target() -> io::Result<()> {
+        let path = "/tmp/atomic_abort.txt";
+        fs::write(path, "complete\n")?;
+
+        let temp = {
+            let mut file = AtomicFile::create(path)?;
+            file.write_all(b"partial")?;
+            file.temp_path().to_path_buf()
+        };
+
+        assert_eq!(fs::read_to_string(path)?, "complete\n");
+        assert!(!temp.exists());
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn failed_flush_removes_temp() -> io::Result<()> {
+        let path = "/tmp/atomic_failed_flush.txt";
+        let mut file = AtomicFile::create(path)?;
+        let temp = file.temp_path().to_path_buf();
+
+        // A read-only handle: the buffered data cannot be flushed.
+        file.writer = Some(BufWriter::new(File::open(&temp)?));
+        file.write_all(b"data")?;
+
+        assert!(file.commit().is_err());
+        assert!(!temp.exists());
+        assert!(!Path::new(path).exists());
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_target_permissions_and_symlink() -> io::Result<()> {
+        use std::os::unix::fs::{PermissionsExt, symlink};
+
+        let path = "/tmp/atomic_secret.txt";
+        let link = "/tmp/atomic_secret_link.txt";
+        let _ = fs::remove_file(link);
+        fs::write(path, "old\n")?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        symlink(path, link)?;
+
+        let mut file = AtomicFile::create(link)?;
+        file.write_all(b"new\n")?;
+        file.commit()?;
+
+        assert!(fs::symlink_metadata(link)?.is_symlink());
+        assert_eq!(fs::read_to_string(path)?, "new\n");
+        assert_eq!(fs::metadata(path)?.permissions().mode() & 0o777, 0o600);
+
+        fs::remove_file(link)?;
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn relative_path_in_current_dir() -> io::Result<()> {
+        let file = AtomicFile::create("atomic_relative.txt")?;
+        assert_eq!(file.temp_path().parent(), Some(Path::new("")));
+        drop(file);
+        assert!(!Path::new("atomic_relative.txt").exists());
+        Ok(())
+    }
+}
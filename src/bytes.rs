@@ -0,0 +1,624 @@
+//! # Byte Slice Toolkit
+//!
+//! Extensions for `&[u8]`, used by the fast paths that parse SPED lines
+//! as bytes to avoid UTF-8 validation.
+
+use crate::{Base32, Base64, Codec, DecodeError, Hex};
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fmt::{self, Display},
+    iter::FusedIterator,
+};
+
+/// Byte slice `&[u8]` extension.
+pub trait BytesExtension {
+    /**
+    Trim ascii whitespace from the start and end of `&[u8]`.
+
+    Returns `&[u8]` with leading and trailing whitespace removed.
+
+    Example:
+    ```
+        use claudiofsr_lib::BytesExtension;
+
+        let text1: &str = " foo bar\r\n";
+        let text2: &str = "foo\nbar";
+        let text3: &str = "";
+
+        let bytes1: Vec<u8> = text1.bytes().collect();
+        let bytes2: Vec<u8> = text2.bytes().collect();
+        let bytes3: Vec<u8> = text3.bytes().collect();
+
+        println!("bytes1: {bytes1:?}");
+        println!("bytes2: {bytes2:?}");
+        println!("bytes3: {bytes3:?}");
+
+        let trimmed1: &[u8] = bytes1.trim();
+        let trimmed2: &[u8] = bytes2.trim();
+        let trimmed3: &[u8] = bytes3.trim();
+
+        println!("trimmed1: {trimmed1:?}");
+        println!("trimmed2: {trimmed2:?}");
+        println!("trimmed3: {trimmed3:?}");
+
+        assert_eq!(bytes1, [32, 102, 111, 111, 32, 98, 97, 114, 13, 10]);
+        assert_eq!(bytes2, [102, 111, 111, 10, 98, 97, 114]);
+        assert!(bytes3.is_empty());
+
+        assert_eq!(trimmed1, [102, 111, 111, 32, 98, 97, 114]);
+        assert_eq!(trimmed2, [102, 111, 111, 10, 98, 97, 114]);
+        assert_eq!(trimmed3, []);
+    ```
+    <https://stackoverflow.com/questions/31101915/how-to-implement-trim-for-vecu8>
+    */
+    fn trim(&self) -> &Self;
+
+    /**
+    Trim ascii whitespace from the start of `&[u8]`.
+
+    Example:
+    ```
+        use claudiofsr_lib::BytesExtension;
+
+        assert_eq!(b" \t foo \n".trim_start(), b"foo \n");
+        assert_eq!(b"   ".trim_start(), b"");
+    ```
+    */
+    fn trim_start(&self) -> &Self;
+
+    /**
+    Trim ascii whitespace from the end of `&[u8]`.
+
+    Example:
+    ```
+        use claudiofsr_lib::BytesExtension;
+
+        assert_eq!(b" foo \r\n".trim_end(), b" foo");
+        assert_eq!(b"   ".trim_end(), b"");
+    ```
+    */
+    fn trim_end(&self) -> &Self;
+
+    /**
+    `&[u8]` to  hex string
+
+    Example:
+    ```
+        use claudiofsr_lib::BytesExtension;
+
+        let text: &str = " foo bar\n";
+        let bytes: Vec<u8> = text.bytes().collect();
+
+        println!("bytes: {bytes:?}");
+
+        let string = bytes.to_hex_string();
+
+        println!("string: {string:?}");
+
+        assert_eq!(bytes, [32, 102, 111, 111, 32, 98, 97, 114, 10]);
+        assert_eq!(string, "20666f6f206261720a");
+    ```
+    */
+    fn to_hex_string(&self) -> String;
+
+    /// `&[u8]` to upper case hex string
+    fn to_hex_upper_string(&self) -> String;
+
+    /**
+    `&[u8]` to base64 string (standard alphabet, padded)
+
+    Example:
+    ```
+        use claudiofsr_lib::BytesExtension;
+
+        assert_eq!(b"NF-e".to_base64_string(), "TkYtZQ==");
+    ```
+    */
+    fn to_base64_string(&self) -> String;
+
+    /// `&[u8]` to base32 string (RFC 4648 standard alphabet, padded)
+    fn to_base32_string(&self) -> String;
+
+    /**
+    Encode with any [`Codec`]
+
+    Example:
+    ```
+        use claudiofsr_lib::{Base64, BytesExtension};
+
+        let qr_payload: &[u8] = &[0xFB, 0xFF];
+        assert_eq!(qr_payload.encode_with(Base64::URL_SAFE_NO_PAD), "-_8");
+    ```
+    */
+    fn encode_with<C: Codec>(&self, codec: C) -> String;
+
+    /**
+    Decode the bytes (encoded text) with any [`Codec`]
+
+    Example:
+    ```
+        use claudiofsr_lib::{BytesExtension, DecodeError, Hex};
+
+        assert_eq!(b"cafe".decode_with(Hex::Lower).unwrap(), [0xCA, 0xFE]);
+        assert_eq!(
+            b"cafx".decode_with(Hex::Lower),
+            Err(DecodeError::InvalidByte { index: 3, byte: b'x' })
+        );
+    ```
+    */
+    fn decode_with<C: Codec>(&self, codec: C) -> Result<Vec<u8>, DecodeError>;
+
+    /**
+    Split `&[u8]` on a delimiter byte, borrowing each field.
+
+    Like `str::split(char)`: a leading or trailing delimiter yields an empty field.
+
+    Example:
+    ```
+        use claudiofsr_lib::BytesExtension;
+
+        let line: &[u8] = b"|C170|1|7894900011517||";
+        let fields: Vec<&[u8]> = line.split_on(b'|').collect();
+
+        assert_eq!(fields, [&b""[..], b"C170", b"1", b"7894900011517", b"", b""]);
+
+        // Fields of the record, without the outer delimiters:
+        let mut fields = line.strip_prefix_and_suffix(b'|').split_on(b'|');
+        assert_eq!(fields.next(), Some(&b"C170"[..]));
+        assert_eq!(fields.next_back(), Some(&b""[..]));
+    ```
+    */
+    fn split_on(&self, delimiter: u8) -> SplitOn<'_>;
+
+    /**
+    Returns `&[u8]` with everything up to the first delimiter and from the
+    last delimiter removed.
+
+    Mirrors [`StrExtension::strip_prefix_and_sufix`](crate::StrExtension::strip_prefix_and_sufix).
+    Returns `self` if there is no delimiter, and an empty slice if there is only one.
+
+    Example:
+    ```
+        use claudiofsr_lib::BytesExtension;
+
+        assert_eq!(b"|C100|0|1|".strip_prefix_and_suffix(b'|'), b"C100|0|1");
+        assert_eq!(b"12|34|ab|5|678".strip_prefix_and_suffix(b'|'), b"34|ab|5");
+        assert_eq!(b"C100".strip_prefix_and_suffix(b'|'), b"C100");
+        assert_eq!(b"C1|00".strip_prefix_and_suffix(b'|'), b"");
+    ```
+    */
+    fn strip_prefix_and_suffix(&self, delimiter: u8) -> &Self;
+
+    /**
+    Returns the index of the first occurrence of `needle`.
+
+    An empty `needle` is found at index 0.
+
+    Example:
+    ```
+        use claudiofsr_lib::BytesExtension;
+
+        let xml: &[u8] = b"<infNFe Id=\"NFe3524\"><ide>";
+        assert_eq!(xml.find_subslice(b"Id=\""), Some(8));
+        assert_eq!(xml.find_subslice(b"<emit>"), None);
+    ```
+    */
+    fn find_subslice(&self, needle: &[u8]) -> Option<usize>;
+
+    /**
+    Returns true if `&[u8]` starts with `prefix`, ignoring ASCII case.
+
+    For equality, use the inherent `<[u8]>::eq_ignore_ascii_case`.
+
+    Example:
+    ```
+        use claudiofsr_lib::BytesExtension;
+
+        assert!(b"<?XML version".starts_with_ignore_ascii_case(b"<?xml"));
+        assert!(!b"<?x".starts_with_ignore_ascii_case(b"<?xml"));
+    ```
+    */
+    fn starts_with_ignore_ascii_case(&self, prefix: &[u8]) -> bool;
+
+    /**
+    Returns true if `&[u8]` ends with `suffix`, ignoring ASCII case.
+
+    Example:
+    ```
+        use claudiofsr_lib::BytesExtension;
+
+        assert!(b"nota.XML".ends_with_ignore_ascii_case(b".xml"));
+        assert!(!b"nota.txt".ends_with_ignore_ascii_case(b".xml"));
+    ```
+    */
+    fn ends_with_ignore_ascii_case(&self, suffix: &[u8]) -> bool;
+
+    /**
+    Compares lexicographically, ignoring ASCII case.
+
+    Example:
+    ```
+        use claudiofsr_lib::BytesExtension;
+        use std::cmp::Ordering;
+
+        assert_eq!(b"c170".cmp_ignore_ascii_case(b"C170"), Ordering::Equal);
+        assert_eq!(b"a".cmp_ignore_ascii_case(b"B"), Ordering::Less);
+        assert_eq!(b"C1".cmp_ignore_ascii_case(b"c"), Ordering::Greater);
+    ```
+    */
+    fn cmp_ignore_ascii_case(&self, other: &[u8]) -> Ordering;
+
+    /**
+    Parses ASCII decimal digits into an integer, without allocating a `String`.
+
+    As in `str::parse`, a leading `+` is accepted, and signed types also
+    accept a leading `-`. Whitespace is not skipped
+    (combine with [`BytesExtension::trim`]).
+
+    Example:
+    ```
+        use claudiofsr_lib::{BytesExtension, ParseDigitsError};
+
+        assert_eq!(b"00123".parse_ascii_digits::<u32>(), Ok(123));
+        assert_eq!(b"-45".parse_ascii_digits::<i16>(), Ok(-45));
+        assert_eq!(b" 7 ".trim().parse_ascii_digits::<u8>(), Ok(7));
+
+        assert_eq!(b"256".parse_ascii_digits::<u8>(), Err(ParseDigitsError::Overflow));
+        assert_eq!(
+            b"12x".parse_ascii_digits::<u64>(),
+            Err(ParseDigitsError::InvalidDigit { index: 2, byte: b'x' })
+        );
+    ```
+    */
+    fn parse_ascii_digits<T: AsciiInteger>(&self) -> Result<T, ParseDigitsError>;
+}
+
+impl BytesExtension for [u8] {
+    fn trim(&self) -> &[u8] {
+        let from = match self.iter().position(|b| !b.is_ascii_whitespace()) {
+            Some(index) => index,
+            None => return &self[..0],
+        };
+        let to = self.iter().rposition(|b| !b.is_ascii_whitespace()).unwrap();
+        &self[from..=to]
+    }
+
+    fn trim_start(&self) -> &[u8] {
+        match self.iter().position(|b| !b.is_ascii_whitespace()) {
+            Some(index) => &self[index..],
+            None => &self[..0],
+        }
+    }
+
+    fn trim_end(&self) -> &[u8] {
+        match self.iter().rposition(|b| !b.is_ascii_whitespace()) {
+            Some(index) => &self[..=index],
+            None => &self[..0],
+        }
+    }
+
+    fn to_hex_string(&self) -> String {
+        Hex::Lower.encode(self)
+    }
+
+    fn to_hex_upper_string(&self) -> String {
+        Hex::Upper.encode(self)
+    }
+
+    fn to_base64_string(&self) -> String {
+        Base64::STANDARD.encode(self)
+    }
+
+    fn to_base32_string(&self) -> String {
+        Base32::STANDARD.encode(self)
+    }
+
+    fn encode_with<C: Codec>(&self, codec: C) -> String {
+        codec.encode(self)
+    }
+
+    fn decode_with<C: Codec>(&self, codec: C) -> Result<Vec<u8>, DecodeError> {
+        codec.decode(self)
+    }
+
+    fn split_on(&self, delimiter: u8) -> SplitOn<'_> {
+        SplitOn {
+            remainder: Some(self),
+            delimiter,
+        }
+    }
+
+    fn strip_prefix_and_suffix(&self, delimiter: u8) -> &[u8] {
+        let from = match self.iter().position(|&b| b == delimiter) {
+            Some(index) => index + 1,
+            None => return self,
+        };
+        let to = self.iter().rposition(|&b| b == delimiter).unwrap();
+        &self[from.min(to)..to]
+    }
+
+    fn find_subslice(&self, needle: &[u8]) -> Option<usize> {
+        if needle.is_empty() {
+            return Some(0);
+        }
+        self.windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    fn starts_with_ignore_ascii_case(&self, prefix: &[u8]) -> bool {
+        self.len() >= prefix.len() && self[..prefix.len()].eq_ignore_ascii_case(prefix)
+    }
+
+    fn ends_with_ignore_ascii_case(&self, suffix: &[u8]) -> bool {
+        self.len() >= suffix.len() && self[self.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+    }
+
+    fn cmp_ignore_ascii_case(&self, other: &[u8]) -> Ordering {
+        self.iter()
+            .map(u8::to_ascii_lowercase)
+            .cmp(other.iter().map(u8::to_ascii_lowercase))
+    }
+
+    fn parse_ascii_digits<T: AsciiInteger>(&self) -> Result<T, ParseDigitsError> {
+        T::from_ascii_digits(self)
+    }
+}
+
+/// Iterator over the fields of `&[u8]` separated by a delimiter byte.
+///
+/// Created by [`BytesExtension::split_on`].
+#[derive(Debug, Clone)]
+pub struct SplitOn<'a> {
+    /// `None` after the last field.
+    remainder: Option<&'a [u8]>,
+    delimiter: u8,
+}
+
+impl<'a> SplitOn<'a> {
+    /// Returns the part not yet iterated (`None` if finished).
+    pub fn remainder(&self) -> Option<&'a [u8]> {
+        self.remainder
+    }
+}
+
+impl<'a> Iterator for SplitOn<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let bytes = self.remainder?;
+        match bytes.iter().position(|&b| b == self.delimiter) {
+            Some(index) => {
+                self.remainder = Some(&bytes[index + 1..]);
+                Some(&bytes[..index])
+            }
+            None => self.remainder.take(),
+        }
+    }
+}
+
+impl DoubleEndedIterator for SplitOn<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let bytes = self.remainder?;
+        match bytes.iter().rposition(|&b| b == self.delimiter) {
+            Some(index) => {
+                self.remainder = Some(&bytes[..index]);
+                Some(&bytes[index + 1..])
+            }
+            None => self.remainder.take(),
+        }
+    }
+}
+
+impl FusedIterator for SplitOn<'_> {}
+
+/// Error returned by [`BytesExtension::parse_ascii_digits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseDigitsError {
+    /// No digits (empty input, or only a sign).
+    Empty,
+    /// A byte that is not an ASCII decimal digit.
+    InvalidDigit {
+        /// Position in the input.
+        index: usize,
+        /// The invalid byte.
+        byte: u8,
+    },
+    /// The number does not fit in the target type.
+    Overflow,
+}
+
+impl Display for ParseDigitsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseDigitsError::Empty => write!(f, "no digits to parse"),
+            ParseDigitsError::InvalidDigit { index, byte } => {
+                write!(f, "invalid digit {:?} at index {index}", char::from(*byte))
+            }
+            ParseDigitsError::Overflow => write!(f, "number too large for the target type"),
+        }
+    }
+}
+
+impl Error for ParseDigitsError {}
+
+/// Integer types supported by [`BytesExtension::parse_ascii_digits`].
+pub trait AsciiInteger: Sized {
+    /// Parses ASCII decimal digits.
+    fn from_ascii_digits(bytes: &[u8]) -> Result<Self, ParseDigitsError>;
+}
+
+/// Returns the value of an ASCII digit.
+fn digit_at(bytes: &[u8], index: usize) -> Result<u8, ParseDigitsError> {
+    let byte = bytes[index];
+    if byte.is_ascii_digit() {
+        Ok(byte - b'0')
+    } else {
+        Err(ParseDigitsError::InvalidDigit { index, byte })
+    }
+}
+
+macro_rules! impl_ascii_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl AsciiInteger for $t {
+                fn from_ascii_digits(bytes: &[u8]) -> Result<Self, ParseDigitsError> {
+                    let start = usize::from(bytes.first() == Some(&b'+'));
+                    if bytes.len() == start {
+                        return Err(ParseDigitsError::Empty);
+                    }
+                    let mut value: $t = 0;
+                    for index in start..bytes.len() {
+                        let digit = digit_at(bytes, index)?;
+                        value = value
+                            .checked_mul(10)
+                            .and_then(|value| value.checked_add(digit.into()))
+                            .ok_or(ParseDigitsError::Overflow)?;
+                    }
+                    Ok(value)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_ascii_signed {
+    ($($t:ty),*) => {
+        $(
+            impl AsciiInteger for $t {
+                fn from_ascii_digits(bytes: &[u8]) -> Result<Self, ParseDigitsError> {
+                    let (negative, start) = match bytes.first() {
+                        Some(b'-') => (true, 1),
+                        Some(b'+') => (false, 1),
+                        _ => (false, 0),
+                    };
+                    if bytes.len() == start {
+                        return Err(ParseDigitsError::Empty);
+                    }
+                    // Accumulate negative values separately so that MIN does not overflow.
+                    let mut value: $t = 0;
+                    for index in start..bytes.len() {
+                        let digit = digit_at(bytes, index)? as $t;
+                        value = value
+                            .checked_mul(10)
+                            .and_then(|value| {
+                                if negative {
+                                    value.checked_sub(digit)
+                                } else {
+                                    value.checked_add(digit)
+                                }
+                            })
+                            .ok_or(ParseDigitsError::Overflow)?;
+                    }
+                    Ok(value)
+                }
+            }
+        )*
+    };
+}
+
+impl_ascii_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_ascii_signed!(i8, i16, i32, i64, i128, isize);
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output bytes_tests
+
+#[cfg(test)]
+mod bytes_tests {
+    use super::*;
+
+    #[test]
+    fn split_on_matches_str_split() {
+        for text in ["", "|", "a", "|a|", "a||b", "|C100|0|1||2|", "||"] {
+            let expected: Vec<&[u8]> = text.split('|').map(str::as_bytes).collect();
+            let fields: Vec<&[u8]> = text.as_bytes().split_on(b'|').collect();
+            assert_eq!(fields, expected, "text: {text:?}");
+
+            let expected: Vec<&[u8]> = text.rsplit('|').map(str::as_bytes).collect();
+            let fields: Vec<&[u8]> = text.as_bytes().split_on(b'|').rev().collect();
+            assert_eq!(fields, expected, "text: {text:?}");
+        }
+    }
+
+    #[test]
+    fn split_on_from_both_ends() {
+        let mut fields = b"a|b|c".split_on(b'|');
+        assert_eq!(fields.next(), Some(&b"a"[..]));
+        assert_eq!(fields.next_back(), Some(&b"c"[..]));
+        assert_eq!(fields.remainder(), Some(&b"b"[..]));
+        assert_eq!(fields.next_back(), Some(&b"b"[..]));
+        assert_eq!(fields.next(), None);
+        assert_eq!(fields.next_back(), None);
+    }
+
+    #[test]
+    fn parse_ascii_digits_limits() {
+        use ParseDigitsError::*;
+
+        assert_eq!(b"255".parse_ascii_digits::<u8>(), Ok(255));
+        assert_eq!(b"-128".parse_ascii_digits::<i8>(), Ok(-128));
+        assert_eq!(b"128".parse_ascii_digits::<i8>(), Err(Overflow));
+        assert_eq!(
+            b"-9223372036854775808".parse_ascii_digits::<i64>(),
+            Ok(i64::MIN)
+        );
+        assert_eq!(
+            b"9223372036854775808".parse_ascii_digits::<i64>(),
+            Err(Overflow)
+        );
+        assert_eq!(
+            b"340282366920938463463374607431768211455".parse_ascii_digits::<u128>(),
+            Ok(u128::MAX)
+        );
+
+        assert_eq!(b"".parse_ascii_digits::<u32>(), Err(Empty));
+        assert_eq!(b"-".parse_ascii_digits::<i32>(), Err(Empty));
+        assert_eq!(
+            b"-1".parse_ascii_digits::<u32>(),
+            Err(InvalidDigit {
+                index: 0,
+                byte: b'-'
+            })
+        );
+        assert_eq!(
+            b"1 2".parse_ascii_digits::<u32>(),
+            Err(InvalidDigit {
+                index: 1,
+                byte: b' '
+            })
+        );
+
+        // Same results as `str::parse`.
+        for text in ["0", "7", "00042", "65535", "65536", "+1"] {
+            let expected = text.parse::<u16>().ok();
+            assert_eq!(
+                text.as_bytes().parse_ascii_digits::<u16>().ok(),
+                expected,
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn case_insensitive_comparison() {
+        let mut registers: Vec<&[u8]> = vec![b"c170", b"C100", b"0150", b"c010"];
+        registers.sort_by(|a, b| a.cmp_ignore_ascii_case(b));
+        assert_eq!(registers, [&b"0150"[..], b"c010", b"C100", b"c170"]);
+
+        assert!(b"".starts_with_ignore_ascii_case(b""));
+        assert!(b"ABC".ends_with_ignore_ascii_case(b"bc"));
+        assert!(!b"bc".ends_with_ignore_ascii_case(b"abc"));
+    }
+
+    #[test]
+    fn find_subslice_edges() {
+        assert_eq!(b"abc".find_subslice(b""), Some(0));
+        assert_eq!(b"".find_subslice(b"a"), None);
+        assert_eq!(b"ab".find_subslice(b"abc"), None);
+        assert_eq!(b"aab".find_subslice(b"ab"), Some(1));
+        assert_eq!(b"abab".find_subslice(b"ab"), Some(0));
+    }
+}
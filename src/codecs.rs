@@ -0,0 +1,666 @@
+//! # Binary-to-Text Codecs
+//!
+//! Hex (lower and upper case), base64 (standard and URL-safe, padded and
+//! unpadded) and base32 (RFC 4648), with typed decoding errors.
+//!
+//! Every codec implements [`Codec`], which encodes into a new `String`,
+//! appends to an existing one, or writes to any `fmt::Write`.
+//!
+//! Example:
+//! ```
+//!     use claudiofsr_lib::{Base32, Base64, Codec, DecodeError, Hex};
+//!
+//!     let bytes = b"NF-e";
+//!
+//!     assert_eq!(Hex::Upper.encode(bytes), "4E462D65");
+//!     assert_eq!(Base64::STANDARD.encode(bytes), "TkYtZQ==");
+//!     assert_eq!(Base64::URL_SAFE_NO_PAD.encode(bytes), "TkYtZQ");
+//!     assert_eq!(Base32::STANDARD.encode(bytes), "JZDC2ZI=");
+//!
+//!     assert_eq!(Base64::STANDARD.decode("TkYtZQ==").unwrap(), bytes);
+//!     assert_eq!(
+//!         Base64::STANDARD.decode("TkY*ZQ=="),
+//!         Err(DecodeError::InvalidByte { index: 3, byte: b'*' })
+//!     );
+//! ```
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Write},
+};
+
+/// Error returned when decoding invalid input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A byte outside the alphabet, at `index` of the input.
+    InvalidByte {
+        /// Position in the input.
+        index: usize,
+        /// The invalid byte.
+        byte: u8,
+    },
+    /// The number of symbols cannot be produced by the encoder.
+    InvalidLength {
+        /// Number of symbols (ignoring padding and whitespace).
+        length: usize,
+    },
+    /// Missing, misplaced or unexpected `=` padding.
+    InvalidPadding,
+    /// The last symbol has non-zero unused bits (the input is not canonical).
+    TrailingBits,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidByte { index, byte } => {
+                write!(f, "invalid byte {:?} at index {index}", char::from(*byte))
+            }
+            DecodeError::InvalidLength { length } => write!(f, "invalid input length: {length}"),
+            DecodeError::InvalidPadding => write!(f, "invalid padding"),
+            DecodeError::TrailingBits => write!(f, "non-zero trailing bits"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// A binary-to-text encoding.
+pub trait Codec {
+    /// Number of characters produced for `len` input bytes.
+    fn encoded_len(&self, len: usize) -> usize;
+
+    /// Writes the encoded bytes to any `fmt::Write`.
+    fn encode_fmt<W>(&self, bytes: &[u8], out: &mut W) -> fmt::Result
+    where
+        W: Write + ?Sized;
+
+    /**
+    Decodes `text`, appending the bytes to `out`.
+
+    On error, `out` is left unchanged.
+    */
+    fn decode_into(&self, text: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError>;
+
+    /// Encodes into a new `String`.
+    fn encode(&self, bytes: &[u8]) -> String {
+        let mut text = String::with_capacity(self.encoded_len(bytes.len()));
+        self.encode_into(bytes, &mut text);
+        text
+    }
+
+    /// Appends the encoded bytes to an existing `String`.
+    fn encode_into(&self, bytes: &[u8], out: &mut String) {
+        out.reserve(self.encoded_len(bytes.len()));
+        // Writing to a String never fails.
+        let _ = self.encode_fmt(bytes, out);
+    }
+
+    /// Decodes into a new `Vec<u8>`.
+    fn decode<T>(&self, text: T) -> Result<Vec<u8>, DecodeError>
+    where
+        T: AsRef<[u8]>,
+    {
+        let mut bytes = Vec::new();
+        self.decode_into(text.as_ref(), &mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                    Hex                                     //
+//----------------------------------------------------------------------------//
+
+/**
+Hexadecimal (base16) encoding.
+
+Decoding accepts both lower and upper case digits.
+
+Example:
+```
+    use claudiofsr_lib::{Codec, DecodeError, Hex};
+
+    assert_eq!(Hex::Lower.encode(&[0, 171, 255]), "00abff");
+    assert_eq!(Hex::Upper.encode(&[0, 171, 255]), "00ABFF");
+
+    assert_eq!(Hex::Lower.decode("00AbfF").unwrap(), [0, 171, 255]);
+    assert_eq!(Hex::Lower.decode("abc"), Err(DecodeError::InvalidLength { length: 3 }));
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Hex {
+    /// Digits `0-9a-f`.
+    #[default]
+    Lower,
+    /// Digits `0-9A-F`.
+    Upper,
+}
+
+impl Hex {
+    fn alphabet(self) -> &'static [u8; 16] {
+        match self {
+            Hex::Lower => b"0123456789abcdef",
+            Hex::Upper => b"0123456789ABCDEF",
+        }
+    }
+}
+
+const HEX_DECODE: [u8; 256] = decode_table(b"0123456789abcdef", b"0123456789ABCDEF");
+
+impl Codec for Hex {
+    fn encoded_len(&self, len: usize) -> usize {
+        len * 2
+    }
+
+    fn encode_fmt<W>(&self, bytes: &[u8], out: &mut W) -> fmt::Result
+    where
+        W: Write + ?Sized,
+    {
+        let alphabet = self.alphabet();
+        bytes.iter().try_for_each(|byte| {
+            out.write_char(char::from(alphabet[usize::from(byte >> 4)]))?;
+            out.write_char(char::from(alphabet[usize::from(byte & 0x0F)]))
+        })
+    }
+
+    fn decode_into(&self, text: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        if !text.len().is_multiple_of(2) {
+            return Err(DecodeError::InvalidLength { length: text.len() });
+        }
+
+        let value = |index: usize| match HEX_DECODE[usize::from(text[index])] {
+            INVALID => Err(DecodeError::InvalidByte {
+                index,
+                byte: text[index],
+            }),
+            value => Ok(value),
+        };
+
+        let start = out.len();
+        out.reserve(text.len() / 2);
+
+        for index in (0..text.len()).step_by(2) {
+            match (value(index), value(index + 1)) {
+                (Ok(high), Ok(low)) => out.push((high << 4) | low),
+                (Err(error), _) | (_, Err(error)) => {
+                    out.truncate(start);
+                    return Err(error);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                   Base64                                   //
+//----------------------------------------------------------------------------//
+
+const BASE64_STANDARD: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL_SAFE: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+const BASE64_STANDARD_DECODE: [u8; 256] = decode_table(BASE64_STANDARD, BASE64_STANDARD);
+const BASE64_URL_SAFE_DECODE: [u8; 256] = decode_table(BASE64_URL_SAFE, BASE64_URL_SAFE);
+
+/**
+Base64 encoding (RFC 4648), standard or URL-safe alphabet, with or without padding.
+
+Decoding ignores ASCII whitespace, so values wrapped in lines (such as the
+`SignatureValue` of an NF-e XML signature) can be decoded directly.
+Padding is required by the padded variants and rejected by the unpadded ones.
+
+Example:
+```
+    use claudiofsr_lib::{Base64, Codec, DecodeError};
+
+    let digest = [0xFB, 0xFF, 0x3E];
+    assert_eq!(Base64::STANDARD.encode(&digest), "+/8+");
+    assert_eq!(Base64::URL_SAFE.encode(&digest), "-_8-");
+
+    let wrapped = "TWFu\r\nTWE=";
+    assert_eq!(Base64::STANDARD.decode(wrapped).unwrap(), b"ManMa");
+
+    assert_eq!(Base64::STANDARD.decode("TWE"), Err(DecodeError::InvalidPadding));
+    assert_eq!(Base64::STANDARD_NO_PAD.decode("TWE").unwrap(), b"Ma");
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base64 {
+    url_safe: bool,
+    padding: bool,
+}
+
+impl Base64 {
+    /// Standard alphabet (`+` and `/`), padded with `=`.
+    pub const STANDARD: Base64 = Base64 {
+        url_safe: false,
+        padding: true,
+    };
+    /// Standard alphabet (`+` and `/`), without padding.
+    pub const STANDARD_NO_PAD: Base64 = Base64 {
+        url_safe: false,
+        padding: false,
+    };
+    /// URL-safe alphabet (`-` and `_`), padded with `=`.
+    pub const URL_SAFE: Base64 = Base64 {
+        url_safe: true,
+        padding: true,
+    };
+    /// URL-safe alphabet (`-` and `_`), without padding.
+    pub const URL_SAFE_NO_PAD: Base64 = Base64 {
+        url_safe: true,
+        padding: false,
+    };
+
+    fn layout(&self) -> Layout {
+        let (alphabet, decode): (&'static [u8], _) = if self.url_safe {
+            (BASE64_URL_SAFE, &BASE64_URL_SAFE_DECODE)
+        } else {
+            (BASE64_STANDARD, &BASE64_STANDARD_DECODE)
+        };
+        Layout {
+            alphabet,
+            decode,
+            bits: 6,
+            group: 4,
+            padding: self.padding,
+        }
+    }
+}
+
+impl Default for Base64 {
+    fn default() -> Self {
+        Base64::STANDARD
+    }
+}
+
+impl Codec for Base64 {
+    fn encoded_len(&self, len: usize) -> usize {
+        self.layout().encoded_len(len)
+    }
+
+    fn encode_fmt<W>(&self, bytes: &[u8], out: &mut W) -> fmt::Result
+    where
+        W: Write + ?Sized,
+    {
+        self.layout().encode(bytes, out)
+    }
+
+    fn decode_into(&self, text: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        self.layout().decode(text, out)
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                   Base32                                   //
+//----------------------------------------------------------------------------//
+
+const BASE32_STANDARD: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const BASE32_DECODE: [u8; 256] = decode_table(BASE32_STANDARD, BASE32_STANDARD);
+
+/**
+Base32 encoding (RFC 4648 standard alphabet `A-Z2-7`), with or without padding.
+
+Decoding ignores ASCII whitespace and, like [`Base64`], requires padding only
+in the padded variant.
+
+Example:
+```
+    use claudiofsr_lib::{Base32, Codec};
+
+    assert_eq!(Base32::STANDARD.encode(b"foobar"), "MZXW6YTBOI======");
+    assert_eq!(Base32::NO_PAD.encode(b"foobar"), "MZXW6YTBOI");
+    assert_eq!(Base32::NO_PAD.decode("MZXW6YTBOI").unwrap(), b"foobar");
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base32 {
+    padding: bool,
+}
+
+impl Base32 {
+    /// Padded with `=`.
+    pub const STANDARD: Base32 = Base32 { padding: true };
+    /// Without padding.
+    pub const NO_PAD: Base32 = Base32 { padding: false };
+
+    fn layout(&self) -> Layout {
+        Layout {
+            alphabet: BASE32_STANDARD,
+            decode: &BASE32_DECODE,
+            bits: 5,
+            group: 8,
+            padding: self.padding,
+        }
+    }
+}
+
+impl Default for Base32 {
+    fn default() -> Self {
+        Base32::STANDARD
+    }
+}
+
+impl Codec for Base32 {
+    fn encoded_len(&self, len: usize) -> usize {
+        self.layout().encoded_len(len)
+    }
+
+    fn encode_fmt<W>(&self, bytes: &[u8], out: &mut W) -> fmt::Result
+    where
+        W: Write + ?Sized,
+    {
+        self.layout().encode(bytes, out)
+    }
+
+    fn decode_into(&self, text: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        self.layout().decode(text, out)
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                       Shared base64/base32 machinery                       //
+//----------------------------------------------------------------------------//
+
+/// Marks bytes outside the alphabet in a decode table.
+const INVALID: u8 = 0xFF;
+
+/// Builds a byte -> value table accepting the symbols of both alphabets.
+const fn decode_table(first: &[u8], second: &[u8]) -> [u8; 256] {
+    let mut table = [INVALID; 256];
+    let mut i = 0;
+    while i < first.len() {
+        table[first[i] as usize] = i as u8;
+        table[second[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+/// Describes a power-of-two base encoding.
+struct Layout {
+    alphabet: &'static [u8],
+    decode: &'static [u8; 256],
+    /// Bits per symbol.
+    bits: u32,
+    /// Symbols per padded group.
+    group: usize,
+    padding: bool,
+}
+
+impl Layout {
+    fn mask(&self) -> u64 {
+        (1 << self.bits) - 1
+    }
+
+    fn encoded_len(&self, len: usize) -> usize {
+        let symbols = (len * 8).div_ceil(self.bits as usize);
+        if self.padding {
+            symbols.next_multiple_of(self.group)
+        } else {
+            symbols
+        }
+    }
+
+    fn encode<W>(&self, bytes: &[u8], out: &mut W) -> fmt::Result
+    where
+        W: Write + ?Sized,
+    {
+        let mut acc: u64 = 0;
+        let mut nbits: u32 = 0;
+        let mut symbols: usize = 0;
+
+        let mut push = |value: u64, out: &mut W| {
+            symbols += 1;
+            out.write_char(char::from(self.alphabet[value as usize]))
+        };
+
+        for &byte in bytes {
+            acc = (acc << 8) | u64::from(byte);
+            nbits += 8;
+            while nbits >= self.bits {
+                nbits -= self.bits;
+                push((acc >> nbits) & self.mask(), out)?;
+            }
+            acc &= (1 << nbits) - 1;
+        }
+
+        if nbits > 0 {
+            push((acc << (self.bits - nbits)) & self.mask(), out)?;
+        }
+
+        if self.padding {
+            let padded = symbols.next_multiple_of(self.group);
+            (symbols..padded).try_for_each(|_| out.write_char('='))?;
+        }
+
+        Ok(())
+    }
+
+    fn decode(&self, text: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let start = out.len();
+        let result = self.decode_symbols(text, out);
+        if result.is_err() {
+            out.truncate(start);
+        }
+        result
+    }
+
+    fn decode_symbols(&self, text: &[u8], out: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let mut acc: u64 = 0;
+        let mut nbits: u32 = 0;
+        let mut symbols: usize = 0;
+        let mut pads: usize = 0;
+
+        out.reserve(text.len() * self.bits as usize / 8);
+
+        for (index, &byte) in text.iter().enumerate() {
+            if byte.is_ascii_whitespace() {
+                continue;
+            }
+
+            if byte == b'=' {
+                pads += 1;
+                continue;
+            }
+
+            let value = self.decode[usize::from(byte)];
+            if value == INVALID || pads > 0 {
+                return Err(DecodeError::InvalidByte { index, byte });
+            }
+
+            symbols += 1;
+            acc = (acc << self.bits) | u64::from(value);
+            nbits += self.bits;
+            if nbits >= 8 {
+                nbits -= 8;
+                out.push((acc >> nbits) as u8);
+                acc &= (1 << nbits) - 1;
+            }
+        }
+
+        // A final symbol must contribute at least one full byte.
+        if nbits >= self.bits {
+            return Err(DecodeError::InvalidLength { length: symbols });
+        }
+
+        if acc != 0 {
+            return Err(DecodeError::TrailingBits);
+        }
+
+        let expected_pads = if self.padding {
+            symbols.next_multiple_of(self.group) - symbols
+        } else {
+            0
+        };
+
+        if pads != expected_pads {
+            return Err(DecodeError::InvalidPadding);
+        }
+
+        Ok(())
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output codecs_tests
+
+#[cfg(test)]
+mod codecs_tests {
+    use super::*;
+
+    // RFC 4648, section 10.
+    const VECTORS: [(&str, &str, &str, &str); 7] = [
+        ("", "", "", ""),
+        ("f", "66", "Zg==", "MY======"),
+        ("fo", "666F", "Zm8=", "MZXQ===="),
+        ("foo", "666F6F", "Zm9v", "MZXW6==="),
+        ("foob", "666F6F62", "Zm9vYg==", "MZXW6YQ="),
+        ("fooba", "666F6F6261", "Zm9vYmE=", "MZXW6YTB"),
+        ("foobar", "666F6F626172", "Zm9vYmFy", "MZXW6YTBOI======"),
+    ];
+
+    #[test]
+    fn rfc_4648_vectors() -> Result<(), DecodeError> {
+        for (data, hex, base64, base32) in VECTORS {
+            let bytes = data.as_bytes();
+
+            assert_eq!(Hex::Upper.encode(bytes), hex);
+            assert_eq!(Hex::Lower.encode(bytes), hex.to_lowercase());
+            assert_eq!(Base64::STANDARD.encode(bytes), base64);
+            assert_eq!(Base32::STANDARD.encode(bytes), base32);
+
+            assert_eq!(Hex::Lower.decode(hex)?, bytes);
+            assert_eq!(Base64::STANDARD.decode(base64)?, bytes);
+            assert_eq!(Base32::STANDARD.decode(base32)?, bytes);
+
+            let base64 = base64.trim_end_matches('=');
+            let base32 = base32.trim_end_matches('=');
+            assert_eq!(Base64::STANDARD_NO_PAD.encode(bytes), base64);
+            assert_eq!(Base32::NO_PAD.encode(bytes), base32);
+            assert_eq!(Base64::URL_SAFE_NO_PAD.decode(base64)?, bytes);
+            assert_eq!(Base32::NO_PAD.decode(base32)?, bytes);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip_all_lengths() -> Result<(), DecodeError> {
+        let data: Vec<u8> = (0..=255).collect();
+
+        for len in 0..data.len() {
+            let bytes = &data[..len];
+            for codec in [
+                Base64::STANDARD,
+                Base64::STANDARD_NO_PAD,
+                Base64::URL_SAFE,
+                Base64::URL_SAFE_NO_PAD,
+            ] {
+                let text = codec.encode(bytes);
+                assert_eq!(text.len(), codec.encoded_len(len));
+                assert_eq!(codec.decode(&text)?, bytes);
+            }
+            for codec in [Base32::STANDARD, Base32::NO_PAD] {
+                let text = codec.encode(bytes);
+                assert_eq!(text.len(), codec.encoded_len(len));
+                assert_eq!(codec.decode(&text)?, bytes);
+            }
+            assert_eq!(Hex::Upper.decode(Hex::Upper.encode(bytes))?, bytes);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn decoding_errors() {
+        use DecodeError::*;
+
+        assert_eq!(
+            Hex::Lower.decode("0g"),
+            Err(InvalidByte {
+                index: 1,
+                byte: b'g'
+            })
+        );
+        assert_eq!(Hex::Lower.decode("0"), Err(InvalidLength { length: 1 }));
+
+        let b64 = Base64::STANDARD;
+        assert_eq!(
+            b64.decode("Zm9v-A=="),
+            Err(InvalidByte {
+                index: 4,
+                byte: b'-'
+            })
+        );
+        assert_eq!(
+            Base64::URL_SAFE.decode("+A=="),
+            Err(InvalidByte {
+                index: 0,
+                byte: b'+'
+            })
+        );
+        assert_eq!(b64.decode("Zm9vY"), Err(InvalidLength { length: 5 }));
+        assert_eq!(b64.decode("Zh=="), Err(TrailingBits));
+        assert_eq!(b64.decode("Zg="), Err(InvalidPadding));
+        assert_eq!(b64.decode("Zg==="), Err(InvalidPadding));
+        assert_eq!(
+            b64.decode("Zg=A"),
+            Err(InvalidByte {
+                index: 3,
+                byte: b'A'
+            })
+        );
+        assert_eq!(Base64::STANDARD_NO_PAD.decode("Zg=="), Err(InvalidPadding));
+
+        assert_eq!(Base32::STANDARD.decode("MY"), Err(InvalidPadding));
+        assert_eq!(
+            Base32::NO_PAD.decode("MZX"),
+            Err(InvalidLength { length: 3 })
+        );
+        assert_eq!(
+            Base32::NO_PAD.decode("my"),
+            Err(InvalidByte {
+                index: 0,
+                byte: b'm'
+            })
+        );
+
+        println!(
+            "{}",
+            InvalidByte {
+                index: 4,
+                byte: b'-'
+            }
+        );
+    }
+
+    #[test]
+    fn errors_leave_output_unchanged() {
+        let mut out = b"keep".to_vec();
+        assert!(Base64::STANDARD.decode_into(b"Zm9v!", &mut out).is_err());
+        assert!(Hex::Lower.decode_into(b"00zz", &mut out).is_err());
+        assert_eq!(out, b"keep");
+    }
+
+    #[test]
+    fn encode_into_existing_buffers() -> fmt::Result {
+        let mut text = String::from("hash=");
+        Hex::Lower.encode_into(&[0xCA, 0xFE], &mut text);
+        assert_eq!(text, "hash=cafe");
+
+        let mut text = String::new();
+        write!(text, "<DigestValue>")?;
+        Base64::STANDARD.encode_fmt(b"abc", &mut text)?;
+        write!(text, "</DigestValue>")?;
+        assert_eq!(text, "<DigestValue>YWJj</DigestValue>");
+
+        Ok(())
+    }
+}
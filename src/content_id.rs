@@ -0,0 +1,520 @@
+//! # Content Identifiers
+//!
+//! Stable BLAKE3 identifiers for records and documents, for dedup and
+//! change detection across runs.
+//!
+//! Structured values are hashed through a canonical field encoding
+//! (see [`Canonical`]): every field is written with a type tag and,
+//! for variable-length data, its length. So `("ab", "c")` and `("a", "bc")`
+//! get different identifiers, which would not happen by hashing the
+//! concatenated text.
+
+use crate::BytesExtension;
+use blake3::{Hasher, HexError};
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
+
+/**
+A 256-bit BLAKE3 content identifier.
+
+`Display` prints 64 lowercase hex digits (see [`BytesExtension::to_hex_string`]),
+and `FromStr` parses them back.
+
+Example:
+```
+    use claudiofsr_lib::ContentId;
+
+    // A C170 record (fields) of a SPED EFD Contribuições file.
+    let record = ("C170", 1u32, "7894900011517", 125.50f64);
+
+    let id = ContentId::of(&record);
+    assert_eq!(id, ContentId::of(&("C170", 1u32, "7894900011517", 125.50f64)));
+    assert_ne!(id, ContentId::of(&("C170", 2u32, "7894900011517", 125.50f64)));
+
+    let hex: String = id.to_string();
+    assert_eq!(hex.len(), 64);
+    assert_eq!(hex.parse::<ContentId>().unwrap(), id);
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ContentId([u8; ContentId::LEN]);
+
+impl ContentId {
+    /// Length in bytes.
+    pub const LEN: usize = blake3::OUT_LEN;
+
+    /// Creates an identifier from raw bytes.
+    pub const fn from_bytes(bytes: [u8; Self::LEN]) -> Self {
+        ContentId(bytes)
+    }
+
+    /// Returns the raw bytes.
+    pub const fn as_bytes(&self) -> &[u8; Self::LEN] {
+        &self.0
+    }
+
+    /// Hashes raw bytes (same result as `blake3::hash`).
+    pub fn hash(data: &[u8]) -> Self {
+        ContentId(*blake3::hash(data).as_bytes())
+    }
+
+    /**
+    Hashes raw bytes with a secret key.
+
+    Without the key, nobody can compute (or check) the identifier of a
+    known content.
+
+    Example:
+    ```
+        use claudiofsr_lib::ContentId;
+
+        let key = [7u8; 32];
+        let id = ContentId::keyed(&key, b"NFe35240112345678000190550010000012341000012345");
+
+        assert_ne!(id, ContentId::keyed(&[8u8; 32], b"NFe35240112345678000190550010000012341000012345"));
+        assert_ne!(id, ContentId::hash(b"NFe35240112345678000190550010000012341000012345"));
+    ```
+    */
+    pub fn keyed(key: &[u8; 32], data: &[u8]) -> Self {
+        ContentId(*blake3::keyed_hash(key, data).as_bytes())
+    }
+
+    /**
+    Hashes raw bytes in the key derivation mode.
+
+    The `context` string separates domains: the same content gets different
+    identifiers in different contexts. It should be hardcoded, globally unique
+    and application-specific, such as `"claudiofsr_lib 2024-01-01 NF-e id"`.
+    */
+    pub fn derive_key(context: &str, data: &[u8]) -> Self {
+        ContentId(blake3::derive_key(context, data))
+    }
+
+    /// Hashes a structured value through its canonical encoding.
+    pub fn of<T>(value: &T) -> Self
+    where
+        T: Canonical + ?Sized,
+    {
+        ContentHasher::new().field(value).finalize()
+    }
+
+    /// Hashes a structured value with a secret key. See [`ContentId::keyed`].
+    pub fn keyed_of<T>(key: &[u8; 32], value: &T) -> Self
+    where
+        T: Canonical + ?Sized,
+    {
+        ContentHasher::new_keyed(key).field(value).finalize()
+    }
+
+    /// Hashes a structured value in a derive-key context. See [`ContentId::derive_key`].
+    pub fn derive_key_of<T>(context: &str, value: &T) -> Self
+    where
+        T: Canonical + ?Sized,
+    {
+        ContentHasher::new_derive_key(context)
+            .field(value)
+            .finalize()
+    }
+}
+
+impl Display for ContentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.to_hex_string())
+    }
+}
+
+impl FromStr for ContentId {
+    type Err = HexError;
+
+    /// Parses 64 hex digits (upper or lower case).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        blake3::Hash::from_hex(s).map(|hash| ContentId(*hash.as_bytes()))
+    }
+}
+
+impl From<blake3::Hash> for ContentId {
+    fn from(hash: blake3::Hash) -> Self {
+        ContentId(*hash.as_bytes())
+    }
+}
+
+impl From<ContentId> for [u8; ContentId::LEN] {
+    fn from(id: ContentId) -> Self {
+        id.0
+    }
+}
+
+/// Type tags of the canonical encoding.
+mod tag {
+    pub const UNIT: u8 = 0;
+    pub const BOOL: u8 = 1;
+    pub const UNSIGNED: u8 = 2;
+    pub const SIGNED: u8 = 3;
+    pub const FLOAT: u8 = 4;
+    pub const CHAR: u8 = 5;
+    pub const STR: u8 = 6;
+    pub const BYTES: u8 = 7;
+    pub const NONE: u8 = 8;
+    pub const SOME: u8 = 9;
+    pub const SEQUENCE: u8 = 10;
+    pub const TUPLE: u8 = 11;
+    #[cfg(feature = "decimal")]
+    pub const DECIMAL: u8 = 12;
+}
+
+/**
+Incremental hasher for the canonical encoding of structured values.
+
+Example:
+```
+    use claudiofsr_lib::{ContentHasher, ContentId};
+
+    let key = [42u8; 32];
+    let id: ContentId = ContentHasher::new_keyed(&key)
+        .field("0150")
+        .field("12345678000190")
+        .bytes(b"\x00\x01")
+        .finalize();
+
+    // Fields are length-prefixed: moving a character between fields changes the id.
+    let other = ContentHasher::new_keyed(&key)
+        .field("01501")
+        .field("2345678000190")
+        .bytes(b"\x00\x01")
+        .finalize();
+
+    assert_ne!(id, other);
+```
+*/
+#[derive(Debug, Clone)]
+pub struct ContentHasher {
+    hasher: Hasher,
+}
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContentHasher {
+    /// Unkeyed hasher.
+    pub fn new() -> Self {
+        ContentHasher {
+            hasher: Hasher::new(),
+        }
+    }
+
+    /// Hasher with a secret key. See [`ContentId::keyed`].
+    pub fn new_keyed(key: &[u8; 32]) -> Self {
+        ContentHasher {
+            hasher: Hasher::new_keyed(key),
+        }
+    }
+
+    /// Hasher in a derive-key context. See [`ContentId::derive_key`].
+    pub fn new_derive_key(context: &str) -> Self {
+        ContentHasher {
+            hasher: Hasher::new_derive_key(context),
+        }
+    }
+
+    /// Adds a field.
+    pub fn field<T>(&mut self, value: &T) -> &mut Self
+    where
+        T: Canonical + ?Sized,
+    {
+        value.encode(self);
+        self
+    }
+
+    /// Adds a byte string field.
+    ///
+    /// `&[u8]` has no [`Canonical`] implementation because it would conflict
+    /// with the implementation for slices of any `Canonical` type.
+    pub fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.tagged(tag::BYTES, bytes)
+    }
+
+    /// Returns the identifier of the fields added so far.
+    pub fn finalize(&self) -> ContentId {
+        ContentId(*self.hasher.finalize().as_bytes())
+    }
+
+    /// Writes a tag followed by fixed-length data.
+    fn fixed(&mut self, tag: u8, data: &[u8]) -> &mut Self {
+        self.hasher.update(&[tag]);
+        self.hasher.update(data);
+        self
+    }
+
+    /// Writes a tag, the length and the data.
+    fn tagged(&mut self, tag: u8, data: &[u8]) -> &mut Self {
+        self.length(tag, data.len());
+        self.hasher.update(data);
+        self
+    }
+
+    /// Writes a tag and a length (or a number of items).
+    fn length(&mut self, tag: u8, len: usize) -> &mut Self {
+        self.fixed(tag, &(len as u64).to_le_bytes())
+    }
+}
+
+/**
+Canonical encoding of a value for [`ContentId`].
+
+- Integers are widened (`u8`..`u128` as `u128`, `i8`..`i128` as `i128`),
+  so the same value gives the same encoding whatever its width.
+- Floats are encoded by their bits, with `-0.0` written as `0.0` and
+  every NaN written as the same NaN.
+- Strings, sequences and tuples are prefixed by their length.
+
+Implement it for your own records by encoding each field in a fixed order:
+
+```
+    use claudiofsr_lib::{Canonical, ContentHasher, ContentId};
+
+    struct Participant {
+        code: String,
+        cnpj: Option<String>,
+        uf: [char; 2],
+    }
+
+    impl Canonical for Participant {
+        fn encode(&self, hasher: &mut ContentHasher) {
+            hasher.field(&self.code).field(&self.cnpj).field(&self.uf);
+        }
+    }
+
+    let participant = Participant { code: "F01".into(), cnpj: None, uf: ['S', 'P'] };
+    let id = ContentId::derive_key_of("example 2024 participant", &participant);
+    assert_eq!(id.to_string().len(), 64);
+```
+*/
+pub trait Canonical {
+    /// Writes the canonical encoding of `self` to the hasher.
+    fn encode(&self, hasher: &mut ContentHasher);
+}
+
+impl<T: Canonical + ?Sized> Canonical for &T {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        (**self).encode(hasher);
+    }
+}
+
+impl Canonical for () {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        hasher.fixed(tag::UNIT, &[]);
+    }
+}
+
+impl Canonical for bool {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        hasher.fixed(tag::BOOL, &[u8::from(*self)]);
+    }
+}
+
+impl Canonical for char {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        hasher.fixed(tag::CHAR, &u32::from(*self).to_le_bytes());
+    }
+}
+
+impl Canonical for str {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        hasher.tagged(tag::STR, self.as_bytes());
+    }
+}
+
+impl Canonical for String {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        self.as_str().encode(hasher);
+    }
+}
+
+macro_rules! impl_canonical_integer {
+    ($tag:expr, $wide:ty: $($t:ty),*) => {
+        $(
+            impl Canonical for $t {
+                fn encode(&self, hasher: &mut ContentHasher) {
+                    hasher.fixed($tag, &<$wide>::from(*self).to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_canonical_integer!(tag::UNSIGNED, u128: u8, u16, u32, u64, u128);
+impl_canonical_integer!(tag::SIGNED, i128: i8, i16, i32, i64, i128);
+
+impl Canonical for usize {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        (*self as u64).encode(hasher);
+    }
+}
+
+impl Canonical for isize {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        (*self as i64).encode(hasher);
+    }
+}
+
+impl Canonical for f64 {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        let value = if self.is_nan() {
+            f64::NAN
+        } else if *self == 0.0 {
+            0.0
+        } else {
+            *self
+        };
+        hasher.fixed(tag::FLOAT, &value.to_bits().to_le_bytes());
+    }
+}
+
+impl Canonical for f32 {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        f64::from(*self).encode(hasher);
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl Canonical for Decimal {
+    /// Trailing zeros are removed, so `1.50` and `1.5` have the same encoding.
+    fn encode(&self, hasher: &mut ContentHasher) {
+        hasher.fixed(tag::DECIMAL, &self.normalize().serialize());
+    }
+}
+
+impl<T: Canonical> Canonical for Option<T> {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        match self {
+            None => {
+                hasher.fixed(tag::NONE, &[]);
+            }
+            Some(value) => {
+                hasher.fixed(tag::SOME, &[]).field(value);
+            }
+        }
+    }
+}
+
+impl<T: Canonical> Canonical for [T] {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        hasher.length(tag::SEQUENCE, self.len());
+        self.iter().for_each(|item| item.encode(hasher));
+    }
+}
+
+impl<T: Canonical, const N: usize> Canonical for [T; N] {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        self.as_slice().encode(hasher);
+    }
+}
+
+impl<T: Canonical> Canonical for Vec<T> {
+    fn encode(&self, hasher: &mut ContentHasher) {
+        self.as_slice().encode(hasher);
+    }
+}
+
+macro_rules! impl_canonical_tuple {
+    ($len:expr => $($name:ident . $index:tt),+) => {
+        impl<$($name: Canonical),+> Canonical for ($($name,)+) {
+            fn encode(&self, hasher: &mut ContentHasher) {
+                hasher.length(tag::TUPLE, $len);
+                $(self.$index.encode(hasher);)+
+            }
+        }
+    };
+}
+
+impl_canonical_tuple!(1 => A.0);
+impl_canonical_tuple!(2 => A.0, B.1);
+impl_canonical_tuple!(3 => A.0, B.1, C.2);
+impl_canonical_tuple!(4 => A.0, B.1, C.2, D.3);
+impl_canonical_tuple!(5 => A.0, B.1, C.2, D.3, E.4);
+impl_canonical_tuple!(6 => A.0, B.1, C.2, D.3, E.4, F.5);
+impl_canonical_tuple!(7 => A.0, B.1, C.2, D.3, E.4, F.5, G.6);
+impl_canonical_tuple!(8 => A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7);
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output content_id_tests
+
+#[cfg(test)]
+mod content_id_tests {
+    use super::*;
+
+    #[test]
+    fn display_and_parse() {
+        let id = ContentId::hash(b"abc");
+        assert_eq!(id.to_string(), blake3::hash(b"abc").to_string());
+        assert_eq!(
+            id.to_string().to_uppercase().parse::<ContentId>().unwrap(),
+            id
+        );
+
+        assert!("abc".parse::<ContentId>().is_err());
+        assert!("zz".repeat(32).parse::<ContentId>().is_err());
+    }
+
+    #[test]
+    fn modes_give_different_ids() {
+        let data = b"|C100|0|1|F01|55|00|001|123|";
+        let key = [1u8; 32];
+
+        let plain = ContentId::hash(data);
+        let keyed = ContentId::keyed(&key, data);
+        let derived = ContentId::derive_key("claudiofsr_lib tests", data);
+
+        assert_ne!(plain, keyed);
+        assert_ne!(plain, derived);
+        assert_ne!(keyed, derived);
+
+        assert_eq!(keyed, ContentId::from(blake3::keyed_hash(&key, data)));
+    }
+
+    #[test]
+    fn canonical_encoding_is_unambiguous() {
+        assert_ne!(ContentId::of(&("ab", "c")), ContentId::of(&("a", "bc")));
+        assert_ne!(ContentId::of(&vec!["a", "b"]), ContentId::of(&("a", "b")));
+        assert_ne!(ContentId::of(&Some(1u8)), ContentId::of(&1u8));
+        assert_ne!(ContentId::of(&1u8), ContentId::of(&1i8));
+        assert_ne!(ContentId::of("1"), ContentId::of(&1u8));
+        assert_ne!(
+            ContentId::of(&[vec![1u8], vec![]]),
+            ContentId::of(&[vec![], vec![1u8]])
+        );
+    }
+
+    #[test]
+    fn canonical_encoding_is_stable() {
+        assert_eq!(ContentId::of(&7u8), ContentId::of(&7u64));
+        assert_eq!(ContentId::of(&-7i16), ContentId::of(&-7i128));
+        assert_eq!(ContentId::of("abc"), ContentId::of(&String::from("abc")));
+        assert_eq!(ContentId::of(&0.0f64), ContentId::of(&-0.0f64));
+        assert_eq!(ContentId::of(&f64::NAN), ContentId::of(&-f64::NAN));
+        assert_eq!(ContentId::of(&1.5f32), ContentId::of(&1.5f64));
+
+        let hasher_id = ContentHasher::new().field(&("C170", 3u32)).finalize();
+        assert_eq!(hasher_id, ContentId::of(&("C170", 3u32)));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn decimal_ignores_trailing_zeros() {
+        use rust_decimal_macros::dec;
+
+        assert_eq!(ContentId::of(&dec!(1.50)), ContentId::of(&dec!(1.5)));
+        assert_ne!(ContentId::of(&dec!(1.5)), ContentId::of(&1.5f64));
+    }
+}
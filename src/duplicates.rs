@@ -0,0 +1,320 @@
+//! # Duplicate File Finder
+//!
+//! Finds files with identical content in three stages, each one only
+//! applied to the candidates left by the previous stage:
+//!
+//! 1. group by file size;
+//! 2. group by a partial BLAKE3 hash of the first and last blocks;
+//! 3. group by the full BLAKE3 hash.
+
+use crate::{Error, Result, hash_file, list_files};
+use blake3::{Hash, Hasher};
+use rayon::prelude::*;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// Number of bytes read from the start and from the end of a file for the partial hash.
+pub const PARTIAL_BLOCK_SIZE: u64 = 4 * 1024;
+
+/// Files with identical content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// The BLAKE3 hash of the content.
+    pub hash: Hash,
+    /// The size of each file, in bytes.
+    pub size: u64,
+    /// The files, in input order.
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// Bytes that could be freed by keeping a single copy.
+    pub fn wasted_bytes(&self) -> u64 {
+        self.size * (self.paths.len() as u64).saturating_sub(1)
+    }
+}
+
+/// Result of [`find_duplicates`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateReport {
+    /// Every file examined, in input order.
+    pub files: Vec<PathBuf>,
+    /// Groups of files with identical content, largest waste first.
+    pub groups: Vec<DuplicateGroup>,
+}
+
+impl DuplicateReport {
+    /// Total bytes that could be freed by keeping one copy of each group.
+    pub fn wasted_bytes(&self) -> u64 {
+        self.groups.iter().map(DuplicateGroup::wasted_bytes).sum()
+    }
+
+    /// Number of files that have at least one copy.
+    pub fn duplicate_count(&self) -> usize {
+        self.groups.iter().map(|group| group.paths.len()).sum()
+    }
+
+    /**
+    Partition into unique and duplicate files.
+
+    Same semantics as [`Partition::partition_dup`](crate::Partition::partition_dup):
+    every copy (including the first one) goes to the duplicate set, and order is preserved.
+
+    Example:
+    ```
+        use claudiofsr_lib::find_duplicates;
+        use std::path::PathBuf;
+
+        let paths = ["/tmp/partition_a.xml", "/tmp/partition_b.xml", "/tmp/partition_c.xml"];
+        std::fs::write(paths[0], "<nfe>1</nfe>").unwrap();
+        std::fs::write(paths[1], "<nfe>2</nfe>").unwrap();
+        std::fs::write(paths[2], "<nfe>1</nfe>").unwrap();
+
+        let report = find_duplicates(paths).unwrap();
+        let (unique, duplicate) = report.partition();
+
+        assert_eq!(unique, [PathBuf::from(paths[1])]);
+        assert_eq!(duplicate, [PathBuf::from(paths[0]), PathBuf::from(paths[2])]);
+        assert_eq!(report.wasted_bytes(), 12);
+
+        paths.iter().for_each(|path| std::fs::remove_file(path).unwrap());
+    ```
+    */
+    pub fn partition(&self) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let duplicates: HashSet<&PathBuf> =
+            self.groups.iter().flat_map(|group| &group.paths).collect();
+
+        self.files
+            .iter()
+            .cloned()
+            .partition(|path| !duplicates.contains(path))
+    }
+}
+
+/**
+Finds files with identical content.
+
+Repeated paths in the input are examined only once.
+
+### Errors
+Returns the first I/O error found, with the path of the file.
+*/
+pub fn find_duplicates<I, P>(paths: I) -> Result<DuplicateReport>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let mut seen = HashSet::new();
+    let files: Vec<PathBuf> = paths
+        .into_iter()
+        .map(|path| path.as_ref().to_path_buf())
+        .filter(|path| seen.insert(path.clone()))
+        .collect();
+
+    // Stage 1: group by size.
+    let sizes: Vec<u64> = files
+        .par_iter()
+        .map(|path| {
+            fs::metadata(path)
+                .map(|metadata| metadata.len())
+                .map_err(|error| Error::io(path, error))
+        })
+        .collect::<Result<_>>()?;
+
+    let mut by_size: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (index, &size) in sizes.iter().enumerate() {
+        by_size.entry(size).or_default().push(index);
+    }
+
+    let candidates: Vec<usize> = by_size
+        .into_values()
+        .filter(|indexes| indexes.len() > 1)
+        .flatten()
+        .collect();
+
+    // Stage 2: group by size and partial hash.
+    let partial: Vec<(usize, Hash)> = candidates
+        .into_par_iter()
+        .map(|index| Ok((index, partial_hash(&files[index], sizes[index])?)))
+        .collect::<Result<_>>()?;
+
+    let candidates: Vec<usize> = group_by_key(partial, |&(index, hash)| (sizes[index], hash))
+        .into_iter()
+        .flatten()
+        .map(|(index, _)| index)
+        .collect();
+
+    // Stage 3: group by full hash.
+    // Small files were read entirely by the partial hash, but a full hash
+    // gives the group a hash that matches `hash_file` and `b3sum`.
+    let full: Vec<(usize, Hash)> = candidates
+        .into_par_iter()
+        .map(|index| {
+            let path = &files[index];
+            let (hash, _size) = hash_file(path).map_err(|error| Error::io(path, error))?;
+            Ok((index, hash))
+        })
+        .collect::<Result<_>>()?;
+
+    let mut groups: Vec<DuplicateGroup> = group_by_key(full, |&(_, hash)| hash)
+        .into_iter()
+        .map(|mut members| {
+            members.sort_unstable_by_key(|&(index, _)| index);
+            let (first, hash) = members[0];
+            DuplicateGroup {
+                hash,
+                size: sizes[first],
+                paths: members
+                    .iter()
+                    .map(|&(index, _)| files[index].clone())
+                    .collect(),
+            }
+        })
+        .collect();
+
+    groups.sort_by(|a, b| {
+        b.wasted_bytes()
+            .cmp(&a.wasted_bytes())
+            .then_with(|| a.paths.cmp(&b.paths))
+    });
+
+    Ok(DuplicateReport { files, groups })
+}
+
+/// Finds files with identical content in a directory and its subdirectories.
+///
+/// See [`find_duplicates`] and [`list_files`].
+pub fn find_duplicates_in_dir<P>(dir: P) -> Result<DuplicateReport>
+where
+    P: AsRef<Path>,
+{
+    find_duplicates(list_files(dir)?)
+}
+
+/// Groups items by key, keeping only groups with more than one item.
+fn group_by_key<T, K, F>(items: Vec<T>, key: F) -> Vec<Vec<T>>
+where
+    K: Eq + std::hash::Hash,
+    F: Fn(&T) -> K,
+{
+    let mut groups: HashMap<K, Vec<T>> = HashMap::new();
+    for item in items {
+        groups.entry(key(&item)).or_default().push(item);
+    }
+    groups
+        .into_values()
+        .filter(|group| group.len() > 1)
+        .collect()
+}
+
+/// Hashes the first and the last [`PARTIAL_BLOCK_SIZE`] bytes of a file.
+fn partial_hash(path: &Path, size: u64) -> Result<Hash> {
+    let read_blocks = || -> io::Result<Hash> {
+        let mut file = File::open(path)?;
+        let mut hasher = Hasher::new();
+        let mut buffer = vec![0; PARTIAL_BLOCK_SIZE as usize];
+
+        let head = size.min(PARTIAL_BLOCK_SIZE) as usize;
+        file.read_exact(&mut buffer[..head])?;
+        hasher.update(&buffer[..head]);
+
+        if size > PARTIAL_BLOCK_SIZE {
+            let tail = (size - PARTIAL_BLOCK_SIZE).min(PARTIAL_BLOCK_SIZE);
+            file.seek(SeekFrom::End(-(tail as i64)))?;
+            file.read_exact(&mut buffer[..tail as usize])?;
+            hasher.update(&buffer[..tail as usize]);
+        }
+
+        Ok(hasher.finalize())
+    };
+
+    read_blocks().map_err(|error| Error::io(path, error))
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output duplicates_tests
+
+#[cfg(test)]
+mod duplicates_tests {
+    use super::*;
+
+    #[test]
+    fn finds_groups_and_wasted_bytes() -> Result<()> {
+        let dir = "/tmp/duplicates_tests";
+        let _ = fs::remove_dir_all(dir);
+        fs::create_dir_all(format!("{dir}/sub"))?;
+
+        let large: Vec<u8> = (0..50_000u32).map(|i| (i % 253) as u8).collect();
+        // Same size, same first and last blocks, different middle.
+        let mut large_variant = large.clone();
+        large_variant[25_000] ^= 0xFF;
+
+        let files: [(&str, &[u8]); 7] = [
+            ("a.xml", b"<nfe>1</nfe>"),
+            ("b.xml", b"<nfe>2</nfe>"),
+            ("sub/c.xml", b"<nfe>1</nfe>"),
+            ("large_1.txt", &large),
+            ("large_2.txt", &large),
+            ("large_3.txt", &large_variant),
+            ("unique.txt", b"unique"),
+        ];
+
+        for (name, data) in files {
+            fs::write(format!("{dir}/{name}"), data)?;
+        }
+
+        let report = find_duplicates_in_dir(dir)?;
+        println!("report: {report:#?}");
+
+        assert_eq!(report.files.len(), 7);
+        assert_eq!(report.groups.len(), 2);
+
+        // Largest waste first.
+        assert_eq!(report.groups[0].size, 50_000);
+        assert_eq!(report.groups[0].hash, blake3::hash(&large));
+        assert_eq!(
+            report.groups[0].paths,
+            [
+                PathBuf::from(format!("{dir}/large_1.txt")),
+                PathBuf::from(format!("{dir}/large_2.txt")),
+            ]
+        );
+        assert_eq!(
+            report.groups[1].paths,
+            [
+                PathBuf::from(format!("{dir}/a.xml")),
+                PathBuf::from(format!("{dir}/sub/c.xml")),
+            ]
+        );
+
+        assert_eq!(report.wasted_bytes(), 50_000 + 12);
+        assert_eq!(report.duplicate_count(), 4);
+
+        let (unique, duplicate) = report.partition();
+        assert_eq!(unique.len(), 3);
+        assert_eq!(duplicate.len(), 4);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn repeated_input_paths_are_not_duplicates() -> Result<()> {
+        let path = "/tmp/duplicates_same_path.txt";
+        fs::write(path, "same")?;
+
+        let report = find_duplicates([path, path])?;
+        assert!(report.groups.is_empty());
+        assert_eq!(report.files.len(), 1);
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+}
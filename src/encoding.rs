@@ -0,0 +1,569 @@
+//! # Text Encoding Utilities
+//!
+//! Detects the encoding of text files (BOM sniffing plus UTF-8 validation)
+//! and transcodes ISO-8859-1 (Latin-1), Windows-1252 and UTF-16 input to UTF-8.
+
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    mem,
+    path::Path,
+    str,
+};
+
+use crate::{Error, Result, open_file};
+
+/// Windows-1252 characters for the bytes `0x80..=0x9F`.
+///
+/// The five undefined positions map to the corresponding C1 control characters,
+/// as in the WHATWG Encoding Standard.
+const WINDOWS_1252: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+/// Number of bytes sampled by [`Encoding::detect`] when reading from files.
+pub const ENCODING_SAMPLE_SIZE: u64 = 64 * 1024;
+
+/// Text encodings supported by the transcoding helpers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// UTF-8, with or without BOM.
+    Utf8,
+    /// UTF-16 little-endian (detected by BOM only).
+    Utf16Le,
+    /// UTF-16 big-endian (detected by BOM only).
+    Utf16Be,
+    /// ISO-8859-1: each byte is the Unicode code point `U+0000..=U+00FF`.
+    Latin1,
+    /// Windows-1252: Latin-1 with printable characters in `0x80..=0x9F`.
+    Windows1252,
+}
+
+impl Encoding {
+    /**
+    Returns the encoding and the length of the Byte Order Mark, if present.
+
+    Example:
+    ```
+        use claudiofsr_lib::Encoding;
+
+        assert_eq!(Encoding::from_bom(b"\xEF\xBB\xBFabc"), Some((Encoding::Utf8, 3)));
+        assert_eq!(Encoding::from_bom(b"\xFF\xFEa\x00"), Some((Encoding::Utf16Le, 2)));
+        assert_eq!(Encoding::from_bom(b"abc"), None);
+    ```
+    */
+    pub fn from_bom(bytes: &[u8]) -> Option<(Encoding, usize)> {
+        match bytes {
+            [0xEF, 0xBB, 0xBF, ..] => Some((Encoding::Utf8, 3)),
+            [0xFF, 0xFE, ..] => Some((Encoding::Utf16Le, 2)),
+            [0xFE, 0xFF, ..] => Some((Encoding::Utf16Be, 2)),
+            _ => None,
+        }
+    }
+
+    /**
+    Detects the encoding of the whole input.
+
+    1. A Byte Order Mark takes precedence;
+    2. Valid UTF-8 is `Utf8`;
+    3. Otherwise the input is `Windows1252` if it has bytes in `0x80..=0x9F`, else `Latin1`.
+
+    For the first bytes of a longer input, use [`Encoding::detect_sample`].
+
+    Example:
+    ```
+        use claudiofsr_lib::Encoding;
+
+        assert_eq!(Encoding::detect("Cláudio".as_bytes()), Encoding::Utf8);
+        assert_eq!(Encoding::detect(b"Cl\xE1udio"), Encoding::Latin1);
+        assert_eq!(Encoding::detect(b"Jos\xE9"), Encoding::Latin1);
+        assert_eq!(Encoding::detect(b"\x93Cl\xE1udio\x94"), Encoding::Windows1252);
+    ```
+    */
+    pub fn detect(bytes: &[u8]) -> Encoding {
+        Encoding::detect_sample(bytes, true)
+    }
+
+    /**
+    Detects the encoding of a sample: the first bytes of the input.
+
+    As [`Encoding::detect`], but if the sample was cut (`at_eof` is false),
+    a UTF-8 sequence truncated at its end is accepted as `Utf8`.
+
+    Example:
+    ```
+        use claudiofsr_lib::Encoding;
+
+        let sample = &"Ação".as_bytes()[..2]; // "A" and half of "ç"
+        assert_eq!(Encoding::detect_sample(sample, false), Encoding::Utf8);
+        assert_eq!(Encoding::detect_sample(sample, true), Encoding::Latin1);
+    ```
+    */
+    pub fn detect_sample(bytes: &[u8], at_eof: bool) -> Encoding {
+        if let Some((encoding, _)) = Encoding::from_bom(bytes) {
+            return encoding;
+        }
+
+        match str::from_utf8(bytes) {
+            Ok(_) => Encoding::Utf8,
+            Err(error) if error.error_len().is_none() && !at_eof => Encoding::Utf8,
+            Err(_) if bytes.iter().any(|b| (0x80..=0x9F).contains(b)) => Encoding::Windows1252,
+            Err(_) => Encoding::Latin1,
+        }
+    }
+
+    /**
+    Decodes bytes in this encoding to UTF-8, removing a leading BOM.
+
+    Invalid UTF-8 sequences are decoded as Windows-1252 and
+    invalid UTF-16 sequences as `U+FFFD`, so decoding never fails.
+
+    Example:
+    ```
+        use claudiofsr_lib::Encoding;
+
+        assert_eq!(Encoding::Latin1.decode(b"A\xE7\xE3o"), "Ação");
+        assert_eq!(Encoding::Windows1252.decode(b"\x80 10"), "€ 10");
+        assert_eq!(Encoding::Utf8.decode(b"\xEF\xBB\xBFabc"), "abc");
+        assert_eq!(Encoding::Utf16Le.decode(b"\xFF\xFEo\x00k\x00"), "ok");
+    ```
+    */
+    pub fn decode(self, bytes: &[u8]) -> Cow<'_, str> {
+        let bytes = match Encoding::from_bom(bytes) {
+            Some((encoding, len)) if encoding == self => &bytes[len..],
+            _ => bytes,
+        };
+
+        if self == Encoding::Utf8
+            && let Ok(text) = str::from_utf8(bytes)
+        {
+            return Cow::Borrowed(text);
+        }
+
+        let mut output = Vec::with_capacity(bytes.len() + bytes.len() / 2);
+        decode_chunk(self, bytes, true, &mut output);
+
+        // decode_chunk only writes valid UTF-8.
+        Cow::Owned(String::from_utf8(output).unwrap_or_default())
+    }
+}
+
+/// Decodes `input` to UTF-8 appending to `output`.
+///
+/// Returns the number of bytes consumed: when `eof` is false, an incomplete
+/// sequence at the end of `input` is left for the next call.
+fn decode_chunk(encoding: Encoding, input: &[u8], eof: bool, output: &mut Vec<u8>) -> usize {
+    match encoding {
+        Encoding::Latin1 => {
+            input.iter().for_each(|&b| push_char(output, b as char));
+            input.len()
+        }
+        Encoding::Windows1252 => {
+            input
+                .iter()
+                .for_each(|&b| push_char(output, windows_1252(b)));
+            input.len()
+        }
+        Encoding::Utf8 => decode_utf8(input, eof, output),
+        Encoding::Utf16Le => decode_utf16(input, eof, output, u16::from_le_bytes),
+        Encoding::Utf16Be => decode_utf16(input, eof, output, u16::from_be_bytes),
+    }
+}
+
+fn decode_utf8(input: &[u8], eof: bool, output: &mut Vec<u8>) -> usize {
+    let mut rest = input;
+
+    loop {
+        match str::from_utf8(rest) {
+            Ok(valid) => {
+                output.extend_from_slice(valid.as_bytes());
+                return input.len();
+            }
+            Err(error) => {
+                let (valid, invalid) = rest.split_at(error.valid_up_to());
+                output.extend_from_slice(valid);
+
+                let len = match error.error_len() {
+                    Some(len) => len,
+                    // Incomplete sequence at the end: wait for more bytes.
+                    None if !eof => return input.len() - invalid.len(),
+                    None => invalid.len(),
+                };
+
+                // Bytes that are not UTF-8 are assumed to be Windows-1252.
+                invalid[..len]
+                    .iter()
+                    .for_each(|&b| push_char(output, windows_1252(b)));
+                rest = &invalid[len..];
+            }
+        }
+    }
+}
+
+fn decode_utf16(
+    input: &[u8],
+    eof: bool,
+    output: &mut Vec<u8>,
+    to_u16: fn([u8; 2]) -> u16,
+) -> usize {
+    let mut units: Vec<u16> = input
+        .chunks_exact(2)
+        .map(|pair| to_u16([pair[0], pair[1]]))
+        .collect();
+
+    let mut consumed = units.len() * 2;
+
+    if !eof {
+        // A high surrogate at the end needs the next unit.
+        if units
+            .last()
+            .is_some_and(|unit| (0xD800..=0xDBFF).contains(unit))
+        {
+            units.pop();
+            consumed -= 2;
+        }
+    }
+
+    char::decode_utf16(units)
+        .map(|result| result.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .for_each(|ch| push_char(output, ch));
+
+    if eof && consumed < input.len() {
+        // Odd trailing byte.
+        push_char(output, char::REPLACEMENT_CHARACTER);
+        consumed = input.len();
+    }
+
+    consumed
+}
+
+fn windows_1252(byte: u8) -> char {
+    match byte {
+        0x80..=0x9F => WINDOWS_1252[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+fn push_char(output: &mut Vec<u8>, ch: char) {
+    let mut buffer = [0; 4];
+    output.extend_from_slice(ch.encode_utf8(&mut buffer).as_bytes());
+}
+
+/**
+A `BufRead` adapter that transcodes its input to UTF-8.
+
+A leading BOM matching the encoding is removed.
+
+Example:
+```
+    use claudiofsr_lib::{DecodeReader, Encoding};
+    use std::io::BufRead;
+
+    let latin1: &[u8] = b"|0000|Jos\xE9|\n|C100|Ma\xE7\xE3|\n";
+
+    let reader = DecodeReader::detect(latin1).unwrap();
+    assert_eq!(reader.encoding(), Encoding::Latin1);
+
+    let lines: Vec<String> = reader.lines().collect::<Result<_, _>>().unwrap();
+    assert_eq!(lines, ["|0000|José|", "|C100|Maçã|"]);
+```
+*/
+pub struct DecodeReader<R> {
+    inner: R,
+    encoding: Encoding,
+    /// Decoded UTF-8 bytes not yet consumed.
+    buffer: Vec<u8>,
+    position: usize,
+    /// Input bytes carried over to the next decoding (incomplete sequences).
+    pending: Vec<u8>,
+    /// Whether the BOM has not been checked yet.
+    at_start: bool,
+}
+
+impl<R: BufRead> DecodeReader<R> {
+    /// Creates a reader that decodes `inner` from the given encoding.
+    pub fn new(inner: R, encoding: Encoding) -> Self {
+        DecodeReader {
+            inner,
+            encoding,
+            buffer: Vec::new(),
+            position: 0,
+            pending: Vec::new(),
+            at_start: true,
+        }
+    }
+
+    /// Creates a reader detecting the encoding from the first buffered bytes.
+    ///
+    /// If they end with an incomplete UTF-8 sequence, more bytes are read
+    /// (up to [`ENCODING_SAMPLE_SIZE`]) to tell UTF-8 from Latin-1.
+    ///
+    /// See [`Encoding::detect_sample`].
+    pub fn detect(mut inner: R) -> io::Result<Self> {
+        let mut sample = Vec::new();
+        let at_eof = loop {
+            let chunk = inner.fill_buf()?;
+            if chunk.is_empty() {
+                break true;
+            }
+            let len = chunk.len();
+            sample.extend_from_slice(chunk);
+            inner.consume(len);
+
+            let truncated = matches!(
+                str::from_utf8(&sample),
+                Err(error) if error.error_len().is_none()
+            );
+            if !truncated || sample.len() as u64 >= ENCODING_SAMPLE_SIZE {
+                break false;
+            }
+        };
+
+        let encoding = Encoding::detect_sample(&sample, at_eof);
+        let mut reader = DecodeReader::new(inner, encoding);
+        // The sample is decoded first.
+        reader.pending = sample;
+        Ok(reader)
+    }
+
+    /// Returns the source encoding.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Unwraps this `DecodeReader`, returning the underlying reader.
+    ///
+    /// Buffered data is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Decodes the next chunk of input into `buffer`.
+    fn refill(&mut self) -> io::Result<()> {
+        self.buffer.clear();
+        self.position = 0;
+
+        loop {
+            let chunk = self.inner.fill_buf()?;
+            let eof = chunk.is_empty();
+            let len = chunk.len();
+
+            self.pending.extend_from_slice(chunk);
+            self.inner.consume(len);
+
+            let input = mem::take(&mut self.pending);
+            let mut start = 0;
+
+            if self.at_start {
+                // Wait for enough bytes to recognize a BOM.
+                if input.len() < 3 && !eof {
+                    self.pending = input;
+                    continue;
+                }
+                if let Some((encoding, bom_len)) = Encoding::from_bom(&input)
+                    && encoding == self.encoding
+                {
+                    start = bom_len;
+                }
+                self.at_start = false;
+            }
+
+            let used = decode_chunk(self.encoding, &input[start..], eof, &mut self.buffer);
+            self.pending.extend_from_slice(&input[start + used..]);
+
+            if eof || !self.buffer.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for DecodeReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.position >= self.buffer.len() {
+            self.refill()?;
+        }
+        Ok(&self.buffer[self.position..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.position = (self.position + amt).min(self.buffer.len());
+    }
+}
+
+/**
+Opens a file for reading as UTF-8, detecting its encoding.
+
+The encoding is detected from the first buffered bytes (see [`Encoding::detect`]).
+Open and read errors are returned as [`Error::Io`], with the path.
+
+Example:
+```
+    use claudiofsr_lib::{open_file_decoded, Encoding};
+    use std::io::{BufRead, Write};
+
+    let path = "/tmp/open_file_decoded.txt";
+    std::fs::File::create(path).unwrap().write_all(b"Jos\xE9\nAn\xE1lise\n").unwrap();
+
+    let reader = open_file_decoded(path).unwrap();
+    assert_eq!(reader.encoding(), Encoding::Latin1);
+
+    let lines: Vec<String> = reader.lines().map(|line| line.unwrap()).collect();
+    assert_eq!(lines, ["José", "Análise"]);
+
+    std::fs::remove_file(path).unwrap();
+```
+*/
+pub fn open_file_decoded<P>(path: P) -> Result<DecodeReader<BufReader<File>>>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let file: File = open_file(path).map_err(|error| Error::io(path, error))?;
+    let reader = BufReader::with_capacity(ENCODING_SAMPLE_SIZE as usize, file);
+    DecodeReader::detect(reader).map_err(|error| Error::io(path, error))
+}
+
+/// Reads the entire file into a UTF-8 `String`, detecting its encoding.
+pub fn read_to_string_decoded<P>(path: P) -> Result<String>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let mut text = String::new();
+    open_file_decoded(path)?
+        .read_to_string(&mut text)
+        .map_err(|error| Error::io(path, error))?;
+    Ok(text)
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output encoding_tests
+
+#[cfg(test)]
+mod encoding_tests {
+    use super::*;
+
+    /// Decodes through a tiny buffer to exercise chunk boundaries.
+    fn decode_with_capacity(bytes: &[u8], encoding: Encoding, capacity: usize) -> String {
+        let inner = BufReader::with_capacity(capacity, bytes);
+        let mut text = String::new();
+        DecodeReader::new(inner, encoding)
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    }
+
+    #[test]
+    fn detect_encodings() {
+        assert_eq!(Encoding::detect(b""), Encoding::Utf8);
+        assert_eq!(Encoding::detect(b"plain ascii"), Encoding::Utf8);
+        assert_eq!(Encoding::detect("ação".as_bytes()), Encoding::Utf8);
+        // UTF-8 sequence truncated at the end of the sample.
+        assert_eq!(
+            Encoding::detect_sample(&"ação".as_bytes()[..2], false),
+            Encoding::Utf8
+        );
+        assert_eq!(Encoding::detect(&"ação".as_bytes()[..2]), Encoding::Latin1);
+        // A trailing Latin-1 byte at the end of the input is not truncated UTF-8.
+        assert_eq!(Encoding::detect(b"caf\xE9"), Encoding::Latin1);
+        assert_eq!(Encoding::detect(b"a\xE7\xE3o"), Encoding::Latin1);
+        assert_eq!(Encoding::detect(b"\x80 a\xE7\xE3o"), Encoding::Windows1252);
+        assert_eq!(Encoding::detect(b"\xFE\xFF\x00a"), Encoding::Utf16Be);
+    }
+
+    #[test]
+    fn decode_windows_1252() {
+        let bytes = b"\x93aspas\x94 \x96 \x80 \x85 \x8A\x9A";
+        assert_eq!(Encoding::Windows1252.decode(bytes), "“aspas” – € … Šš");
+        // Latin-1 maps 0x80..=0x9F to C1 controls.
+        assert_eq!(Encoding::Latin1.decode(b"\x80"), "\u{80}");
+    }
+
+    #[test]
+    fn decode_utf8_with_invalid_bytes() {
+        // Mixed content: invalid bytes fall back to Windows-1252.
+        let mut bytes = "ação ".as_bytes().to_vec();
+        bytes.extend_from_slice(b"a\xE7\xE3o");
+        assert_eq!(Encoding::Utf8.decode(&bytes), "ação ação");
+    }
+
+    #[test]
+    fn decode_reader_across_chunk_boundaries() {
+        let text = "|0000|José|€ 10|🦀|\n|9999|2|\n";
+
+        for capacity in 1..8 {
+            let mut utf8 = vec![0xEF, 0xBB, 0xBF];
+            utf8.extend_from_slice(text.as_bytes());
+            assert_eq!(decode_with_capacity(&utf8, Encoding::Utf8, capacity), text);
+
+            let mut utf16: Vec<u8> = vec![0xFF, 0xFE];
+            text.encode_utf16()
+                .for_each(|unit| utf16.extend_from_slice(&unit.to_le_bytes()));
+            assert_eq!(
+                decode_with_capacity(&utf16, Encoding::Utf16Le, capacity),
+                text
+            );
+
+            let mut utf16: Vec<u8> = vec![0xFE, 0xFF];
+            text.encode_utf16()
+                .for_each(|unit| utf16.extend_from_slice(&unit.to_be_bytes()));
+            assert_eq!(
+                decode_with_capacity(&utf16, Encoding::Utf16Be, capacity),
+                text
+            );
+        }
+
+        let latin1 = b"Jos\xE9\nMa\xE7\xE3\n";
+        assert_eq!(
+            decode_with_capacity(latin1, Encoding::Latin1, 2),
+            "José\nMaçã\n"
+        );
+    }
+
+    #[test]
+    fn decode_reader_detects_truncated_sample() -> io::Result<()> {
+        // Latin-1 ending in an accented byte.
+        let reader = DecodeReader::detect(&b"Jos\xE9"[..])?;
+        assert_eq!(reader.encoding(), Encoding::Latin1);
+
+        // UTF-8 split by a small buffer: more bytes are read before deciding.
+        let text = "ação";
+        let mut reader = DecodeReader::detect(BufReader::with_capacity(2, text.as_bytes()))?;
+        assert_eq!(reader.encoding(), Encoding::Utf8);
+
+        let mut decoded = String::new();
+        reader.read_to_string(&mut decoded)?;
+        assert_eq!(decoded, text);
+        Ok(())
+    }
+
+    #[test]
+    fn read_decoded_file() -> Result<()> {
+        let path = "/tmp/read_to_string_decoded.txt";
+        std::fs::write(path, b"\x93Relat\xF3rio\x94\r\n")?;
+
+        let text = read_to_string_decoded(path)?;
+        assert_eq!(text, "“Relatório”\r\n");
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+}
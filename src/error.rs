@@ -0,0 +1,362 @@
+//! # Error Type
+//!
+//! [`Error`] is the error of every fallible function of this crate
+//! (through [`Result`]), so callers can match on the kind of failure
+//! instead of inspecting messages.
+//!
+//! Errors from `std` and from the dependencies convert to `Error` with `?`,
+//! and `Error` converts to [`MyError`](crate::MyError) (a boxed error).
+
+use crate::{DecodeError, ParseDigitsError, ReadLinesError};
+use std::{
+    fmt::{self, Display},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// `Result` with [`Error`] as the default error type.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/**
+Error type of this crate.
+
+Example:
+```
+    use claudiofsr_lib::{Error, random_in_range, string_to_vec_of_integers};
+
+    match random_in_range(21, 20) {
+        Err(Error::InvalidRange { min, max }) => assert_eq!((min, max), (21, 20)),
+        other => panic!("unexpected: {other:?}"),
+    }
+
+    let error = string_to_vec_of_integers("06 1x 2022").unwrap_err();
+    assert!(matches!(error, Error::Parse { position: Some(3), .. }));
+    assert_eq!(
+        error.to_string(),
+        "invalid digit found in string at position 3: \"1x\""
+    );
+```
+*/
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// An I/O error, with the path of the file (if known).
+    Io {
+        /// The file being read or written.
+        path: Option<PathBuf>,
+        /// The I/O error.
+        source: io::Error,
+    },
+    /// A text that could not be parsed.
+    Parse {
+        /// The invalid text (may be empty if unknown).
+        input: String,
+        /// Position of `input` in the parsed text, if known.
+        position: Option<usize>,
+        /// The parser error message.
+        message: String,
+    },
+    /// A range whose minimum is greater than its maximum.
+    InvalidRange {
+        /// The lower bound.
+        min: i128,
+        /// The upper bound.
+        max: i128,
+    },
+    /// A document (such as a SPED EFD file) that breaks its layout rules.
+    InvalidDocument(String),
+    /// Text that is not valid in the expected encoding (such as UTF-8).
+    Encoding(String),
+    /// Invalid hex, base64 or base32 input.
+    Decode(DecodeError),
+    /// A line of a file that could not be parsed.
+    Lines(ReadLinesError),
+    /// Any other error.
+    Other(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Error {
+    /// Creates an [`Error::Io`] with the path of the file.
+    pub fn io(path: impl AsRef<Path>, source: io::Error) -> Self {
+        Error::Io {
+            path: Some(path.as_ref().to_path_buf()),
+            source,
+        }
+    }
+
+    /**
+    Returns the inner error if it is of type `T`, as `Box<dyn Error>::downcast_ref`.
+
+    Example:
+    ```
+        use claudiofsr_lib::{MyError, ReadLinesError, read_lines_into};
+
+        let path = "/tmp/error_downcast_example.txt";
+        std::fs::write(path, "1\ntwo\n").unwrap();
+
+        let error = read_lines_into::<Vec<u8>, u8>(path).unwrap_err();
+        assert_eq!(error.downcast_ref::<ReadLinesError>().unwrap().line_number, 2);
+
+        // Boxed as MyError: downcast to Error first.
+        let boxed: MyError = error.into();
+        let inner = boxed.downcast_ref::<claudiofsr_lib::Error>().unwrap();
+        assert_eq!(inner.downcast_ref::<ReadLinesError>().unwrap().line, "two");
+
+        std::fs::remove_file(path).unwrap();
+    ```
+    */
+    pub fn downcast_ref<T: std::error::Error + 'static>(&self) -> Option<&T> {
+        let inner: &(dyn std::error::Error + 'static) = match self {
+            Error::Io { source, .. } => source,
+            Error::Decode(error) => error,
+            Error::Lines(error) => error,
+            Error::Other(error) => error.as_ref(),
+            _ => return None,
+        };
+        inner.downcast_ref()
+    }
+
+    /// Creates an [`Error::Parse`].
+    pub fn parse(input: impl Into<String>, position: Option<usize>, message: impl Display) -> Self {
+        Error::Parse {
+            input: input.into(),
+            position,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io {
+                path: Some(path),
+                source,
+            } => write!(f, "{}: {source}", path.display()),
+            Error::Io { path: None, source } => write!(f, "{source}"),
+            Error::Parse {
+                input,
+                position,
+                message,
+            } => {
+                write!(f, "{message}")?;
+                if let Some(position) = position {
+                    write!(f, " at position {position}")?;
+                }
+                if !input.is_empty() {
+                    write!(f, ": {input:?}")?;
+                }
+                Ok(())
+            }
+            Error::InvalidRange { min, max } => {
+                write!(f, "min ({min}) must be less than or equal to max ({max})")
+            }
+            Error::InvalidDocument(message) => write!(f, "invalid document: {message}"),
+            Error::Encoding(message) => write!(f, "invalid encoding: {message}"),
+            Error::Decode(error) => write!(f, "{error}"),
+            Error::Lines(error) => write!(f, "{error}"),
+            Error::Other(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Decode(error) => Some(error),
+            Error::Lines(error) => Some(error),
+            Error::Other(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Self {
+        Error::Io { path: None, source }
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(error: DecodeError) -> Self {
+        Error::Decode(error)
+    }
+}
+
+impl From<ReadLinesError> for Error {
+    fn from(error: ReadLinesError) -> Self {
+        Error::Lines(error)
+    }
+}
+
+impl From<ParseDigitsError> for Error {
+    fn from(error: ParseDigitsError) -> Self {
+        Error::parse("", None, error)
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Other(message.into())
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::Other(message.into())
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for Error {
+    fn from(error: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        Error::Other(error)
+    }
+}
+
+/// Parser errors, converted to [`Error::Parse`] (without the input).
+macro_rules! impl_from_parse_error {
+    ( $($t:ty),* ) => {
+        $(
+            impl From<$t> for Error {
+                fn from(error: $t) -> Self {
+                    Error::parse("", None, error)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_parse_error!(
+    std::num::ParseIntError,
+    std::num::ParseFloatError,
+    std::str::ParseBoolError,
+    chrono::ParseError,
+    blake3::HexError
+);
+
+/// Encoding errors, converted to [`Error::Encoding`].
+macro_rules! impl_from_encoding_error {
+    ( $($t:ty),* ) => {
+        $(
+            impl From<$t> for Error {
+                fn from(error: $t) -> Self {
+                    Error::Encoding(error.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_encoding_error!(std::str::Utf8Error, std::string::FromUtf8Error);
+
+/// Other errors, kept (boxed) in [`Error::Other`].
+macro_rules! impl_from_other_error {
+    ( $($t:ty),* ) => {
+        $(
+            impl From<$t> for Error {
+                fn from(error: $t) -> Self {
+                    Error::Other(Box::new(error))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_other_error!(
+    fmt::Error,
+    std::num::TryFromIntError,
+    indicatif::style::TemplateError,
+    regex::Error
+);
+
+#[cfg(feature = "decimal")]
+impl_from_other_error!(rust_decimal::Error);
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output error_tests
+
+#[cfg(test)]
+mod error_tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn io_error_with_path() {
+        let source = io::Error::new(io::ErrorKind::NotFound, "not found");
+        let error = Error::io("/tmp/missing.txt", source);
+
+        assert_eq!(error.to_string(), "/tmp/missing.txt: not found");
+        assert!(error.source().is_some());
+        assert!(matches!(
+            error,
+            Error::Io { source, .. } if source.kind() == io::ErrorKind::NotFound
+        ));
+    }
+
+    #[test]
+    fn conversions_with_question_mark() {
+        fn parse(text: &str) -> Result<u8, Error> {
+            Ok(text.parse::<u8>()?)
+        }
+        fn utf8(bytes: Vec<u8>) -> Result<String, Error> {
+            Ok(String::from_utf8(bytes)?)
+        }
+
+        assert!(matches!(parse("300"), Err(Error::Parse { .. })));
+        assert!(matches!(utf8(vec![0xff]), Err(Error::Encoding(_))));
+        assert_eq!(Error::from("custom message").to_string(), "custom message");
+    }
+
+    #[test]
+    fn my_error_stays_boxed() {
+        #[derive(Debug)]
+        struct AppError;
+
+        impl Display for AppError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "application error")
+            }
+        }
+
+        impl std::error::Error for AppError {}
+
+        fn run(fail: bool) -> crate::MyResult<u64> {
+            let value = crate::random_in_range(1, 1)?;
+            let other: u64 = "7".parse()?;
+            if fail {
+                Err(AppError)?;
+            }
+            Ok(value + other)
+        }
+
+        assert_eq!(run(false).unwrap(), 8);
+        let error: crate::MyError = run(true).unwrap_err();
+        assert!(error.downcast_ref::<AppError>().is_some());
+
+        let error: crate::MyError = crate::random_in_range(2, 1).unwrap_err().into();
+        assert!(matches!(
+            error.downcast_ref::<Error>(),
+            Some(Error::InvalidRange { min: 2, max: 1 })
+        ));
+    }
+
+    #[test]
+    fn other_error_is_the_source() {
+        let error = Error::from(fmt::Error);
+        assert!(error.source().unwrap().is::<fmt::Error>());
+        assert!(error.downcast_ref::<fmt::Error>().is_some());
+    }
+
+    #[test]
+    fn parse_error_display() {
+        assert_eq!(Error::parse("", None, "no digits").to_string(), "no digits");
+        assert_eq!(
+            Error::parse("3x", Some(7), "invalid digit").to_string(),
+            "invalid digit at position 7: \"3x\""
+        );
+    }
+}
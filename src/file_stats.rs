@@ -0,0 +1,400 @@
+//! # File Statistics
+//!
+//! Single-pass line statistics for validating inbound files: line count,
+//! longest line, blank lines, line endings, BOM and final newline.
+
+use crate::Encoding;
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+#[cfg(feature = "fast-lines")]
+use {memmap2::Mmap, rayon::prelude::*};
+
+/// Size of the blocks read by [`FileStats::from_reader`].
+const BLOCK_SIZE: usize = 256 * 1024;
+
+/// Approximate size of the chunks scanned by each thread with `fast-lines`.
+#[cfg(feature = "fast-lines")]
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Line ending style of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LineEnding {
+    /// No line endings (empty file or a single unterminated line).
+    #[default]
+    None,
+    /// Only `\n`.
+    Lf,
+    /// Only `\r\n`.
+    Crlf,
+    /// Both `\n` and `\r\n`.
+    Mixed,
+}
+
+/**
+Line statistics of a file, computed in a single pass.
+
+Line endings are counted on bytes (`0x0A`), like [`FileExtension::count_lines`](crate::FileExtension::count_lines),
+so the statistics are meaningful for UTF-8, ASCII and single-byte encodings (not UTF-16).
+
+Example:
+```
+    use claudiofsr_lib::{FileStats, LineEnding};
+
+    let path = "/tmp/file_stats_example.txt";
+    std::fs::write(path, "\u{FEFF}|0000|017|\r\n\r\n|9999|3|").unwrap();
+
+    let stats = FileStats::from_path(path).unwrap();
+
+    assert_eq!(stats.lines, 3);
+    assert_eq!(stats.newlines, 2);
+    assert_eq!(stats.longest_line, 10);
+    assert_eq!(stats.blank_lines, 1);
+    assert_eq!(stats.line_ending(), LineEnding::Crlf);
+    assert!(stats.bom.is_some());
+    assert!(!stats.last_line_terminated);
+
+    std::fs::remove_file(path).unwrap();
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStats {
+    /// Total size in bytes, including the BOM.
+    pub bytes: u64,
+    /// Number of lines, including an unterminated last line.
+    pub lines: u64,
+    /// Number of `\n` bytes (the same value as `count_lines`).
+    pub newlines: u64,
+    /// Length in bytes of the longest line, without the line ending and the BOM.
+    pub longest_line: u64,
+    /// Lines that are empty or contain only ASCII whitespace.
+    pub blank_lines: u64,
+    /// Lines ending with `\n` only.
+    pub lf_lines: u64,
+    /// Lines ending with `\r\n`.
+    pub crlf_lines: u64,
+    /// The byte order mark at the start of the file, if any.
+    pub bom: Option<Encoding>,
+    /// True if the file is empty or ends with `\n`.
+    pub last_line_terminated: bool,
+}
+
+impl Default for FileStats {
+    fn default() -> Self {
+        FileStats {
+            bytes: 0,
+            lines: 0,
+            newlines: 0,
+            longest_line: 0,
+            blank_lines: 0,
+            lf_lines: 0,
+            crlf_lines: 0,
+            bom: None,
+            last_line_terminated: true,
+        }
+    }
+}
+
+impl FileStats {
+    /// Returns the line ending style.
+    pub fn line_ending(&self) -> LineEnding {
+        match (self.lf_lines > 0, self.crlf_lines > 0) {
+            (false, false) => LineEnding::None,
+            (true, false) => LineEnding::Lf,
+            (false, true) => LineEnding::Crlf,
+            (true, true) => LineEnding::Mixed,
+        }
+    }
+
+    /// Computes the statistics of a byte slice.
+    ///
+    /// With the `fast-lines` feature, large slices are split at line
+    /// boundaries and scanned in parallel.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let bom = Encoding::from_bom(bytes);
+        let content = &bytes[bom.map_or(0, |(_, len)| len)..];
+
+        let mut stats = scan_content(content);
+        stats.bytes = bytes.len() as u64;
+        stats.bom = bom.map(|(encoding, _)| encoding);
+        stats
+    }
+
+    /// Computes the statistics of any reader, reading it in large blocks.
+    pub fn from_reader<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut buffer = vec![0u8; BLOCK_SIZE];
+        let mut scanner = Scanner::default();
+
+        let mut n = read_block(&mut reader, &mut buffer)?;
+        let bom = Encoding::from_bom(&buffer[..n]);
+        let mut bytes = n as u64;
+        scanner.feed(&buffer[bom.map_or(0, |(_, len)| len)..n]);
+
+        while n > 0 {
+            n = read_block(&mut reader, &mut buffer)?;
+            bytes += n as u64;
+            scanner.feed(&buffer[..n]);
+        }
+
+        let mut stats = scanner.finish();
+        stats.bytes = bytes;
+        stats.bom = bom.map(|(encoding, _)| encoding);
+        Ok(stats)
+    }
+
+    /// Computes the statistics of a file.
+    ///
+    /// See [`FileExtension::file_stats`](crate::FileExtension::file_stats).
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        file_stats(&File::open(path)?)
+    }
+
+    /// Adds the statistics of the following lines (`other` starts at a line start).
+    #[cfg(feature = "fast-lines")]
+    fn append(&mut self, other: &FileStats) {
+        self.bytes += other.bytes;
+        self.lines += other.lines;
+        self.newlines += other.newlines;
+        self.longest_line = self.longest_line.max(other.longest_line);
+        self.blank_lines += other.blank_lines;
+        self.lf_lines += other.lf_lines;
+        self.crlf_lines += other.crlf_lines;
+        self.last_line_terminated = other.last_line_terminated;
+    }
+}
+
+/// Computes the statistics of a file: see [`FileExtension::file_stats`](crate::FileExtension::file_stats).
+#[cfg(feature = "fast-lines")]
+pub(crate) fn file_stats(file: &File) -> io::Result<FileStats> {
+    // Safety: the file must not be modified while mapped (see FileExtension::count_lines).
+    let mmap = unsafe { Mmap::map(file)? };
+    Ok(FileStats::from_bytes(&mmap))
+}
+
+/// Computes the statistics of a file: see [`FileExtension::file_stats`](crate::FileExtension::file_stats).
+#[cfg(not(feature = "fast-lines"))]
+pub(crate) fn file_stats(file: &File) -> io::Result<FileStats> {
+    FileStats::from_reader(file)
+}
+
+/// Scans the content after the BOM.
+#[cfg(not(feature = "fast-lines"))]
+fn scan_content(content: &[u8]) -> FileStats {
+    let mut scanner = Scanner::default();
+    scanner.feed(content);
+    scanner.finish()
+}
+
+/// Scans the content after the BOM, splitting it at line boundaries
+/// into chunks that are scanned in parallel.
+#[cfg(feature = "fast-lines")]
+fn scan_content(content: &[u8]) -> FileStats {
+    let mut chunks = Vec::new();
+    let mut rest = content;
+
+    while rest.len() > CHUNK_SIZE {
+        let end = match rest[CHUNK_SIZE..].iter().position(|&b| b == b'\n') {
+            Some(index) => CHUNK_SIZE + index + 1,
+            None => rest.len(),
+        };
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    if !rest.is_empty() || chunks.is_empty() {
+        chunks.push(rest);
+    }
+
+    let partial: Vec<FileStats> = chunks
+        .par_iter()
+        .map(|chunk| {
+            let mut scanner = Scanner::default();
+            scanner.feed(chunk);
+            scanner.finish()
+        })
+        .collect();
+
+    partial
+        .iter()
+        .fold(FileStats::default(), |mut stats, chunk_stats| {
+            stats.append(chunk_stats);
+            stats
+        })
+}
+
+/// Reads until the buffer is full or the end of the input.
+fn read_block<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    Ok(filled)
+}
+
+/// Streaming line scanner: the current line may span several `feed` calls.
+#[derive(Debug, Default)]
+struct Scanner {
+    stats: FileStats,
+    /// Length of the current (unterminated) line.
+    line_len: u64,
+    /// True while the current line has only whitespace.
+    line_blank: bool,
+    /// True if the last byte of the current line is `\r`.
+    line_ends_with_cr: bool,
+}
+
+impl Scanner {
+    fn feed(&mut self, mut bytes: &[u8]) {
+        self.stats.bytes += bytes.len() as u64;
+
+        while !bytes.is_empty() {
+            let (segment, terminated) = match bytes.iter().position(|&b| b == b'\n') {
+                Some(index) => (&bytes[..index], true),
+                None => (bytes, false),
+            };
+
+            if !segment.is_empty() {
+                if self.line_len == 0 {
+                    self.line_blank = true;
+                }
+                self.line_len += segment.len() as u64;
+                self.line_blank = self.line_blank && segment.iter().all(u8::is_ascii_whitespace);
+                self.line_ends_with_cr = segment.last() == Some(&b'\r');
+            }
+
+            if terminated {
+                self.end_line(true);
+                bytes = &bytes[segment.len() + 1..];
+            } else {
+                bytes = &[];
+            }
+        }
+    }
+
+    /// Records the current line.
+    fn end_line(&mut self, terminated: bool) {
+        let stats = &mut self.stats;
+        let crlf = terminated && self.line_ends_with_cr;
+        let len = self.line_len - u64::from(crlf);
+
+        stats.lines += 1;
+        stats.longest_line = stats.longest_line.max(len);
+        stats.blank_lines += u64::from(self.line_len == 0 || self.line_blank);
+
+        if terminated {
+            stats.newlines += 1;
+            if crlf {
+                stats.crlf_lines += 1;
+            } else {
+                stats.lf_lines += 1;
+            }
+        }
+
+        self.line_len = 0;
+        self.line_blank = false;
+        self.line_ends_with_cr = false;
+    }
+
+    fn finish(mut self) -> FileStats {
+        self.stats.last_line_terminated = self.line_len == 0;
+        if self.line_len > 0 {
+            self.end_line(false);
+        }
+        self.stats
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output file_stats_tests
+// cargo test --features fast-lines -- --show-output file_stats_tests
+
+#[cfg(test)]
+mod file_stats_tests {
+    use super::*;
+    use crate::FileExtension;
+
+    #[test]
+    fn line_endings_and_blank_lines() {
+        let cases: [(&[u8], u64, u64, LineEnding, bool); 7] = [
+            (b"", 0, 0, LineEnding::None, true),
+            (b"abc", 1, 0, LineEnding::None, false),
+            (b"a\nb\n", 2, 0, LineEnding::Lf, true),
+            (b"a\r\n \t\r\n", 2, 1, LineEnding::Crlf, true),
+            (b"a\r\nb\n\n", 3, 1, LineEnding::Mixed, true),
+            (b"\n\n\nx", 4, 3, LineEnding::Lf, false),
+            (b"\r", 1, 1, LineEnding::None, false),
+        ];
+
+        for (bytes, lines, blank_lines, ending, terminated) in cases {
+            let stats = FileStats::from_bytes(bytes);
+            println!("{bytes:?}: {stats:?}");
+            assert_eq!(stats.lines, lines);
+            assert_eq!(stats.blank_lines, blank_lines);
+            assert_eq!(stats.line_ending(), ending);
+            assert_eq!(stats.last_line_terminated, terminated);
+            assert_eq!(stats.bytes, bytes.len() as u64);
+        }
+    }
+
+    #[test]
+    fn longest_line_excludes_line_ending_and_bom() {
+        let stats = FileStats::from_bytes(b"\xEF\xBB\xBFabcd\r\nab\n\r\r\n");
+        assert_eq!(stats.bom, Some(Encoding::Utf8));
+        assert_eq!(stats.longest_line, 4);
+        assert_eq!(stats.crlf_lines, 2);
+        assert_eq!(stats.lf_lines, 1);
+    }
+
+    #[test]
+    fn reader_matches_bytes_across_block_boundaries() -> io::Result<()> {
+        // Lines crossing the block size, with '\r' and '\n' split between blocks.
+        let mut data = b"\xEF\xBB\xBF".to_vec();
+        for i in 0..40_000u32 {
+            let line = "x".repeat((i % 50) as usize);
+            let ending = if i % 3 == 0 { "\r\n" } else { "\n" };
+            data.extend_from_slice(line.as_bytes());
+            data.extend_from_slice(ending.as_bytes());
+        }
+        data.extend_from_slice(&vec![b'y'; BLOCK_SIZE + 10]);
+
+        let from_bytes = FileStats::from_bytes(&data);
+        let from_reader = FileStats::from_reader(&data[..])?;
+        assert_eq!(from_bytes, from_reader);
+        assert_eq!(from_bytes.lines, 40_001);
+        assert_eq!(from_bytes.longest_line, (BLOCK_SIZE + 10) as u64);
+        assert_eq!(from_bytes.line_ending(), LineEnding::Mixed);
+
+        let path = "/tmp/file_stats_large.txt";
+        std::fs::write(path, &data)?;
+        assert_eq!(File::open(path)?.file_stats()?, from_bytes);
+        assert_eq!(File::open(path)?.count_lines()?, from_bytes.newlines);
+        std::fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "fast-lines")]
+    #[test]
+    fn parallel_chunks_match_sequential_scan() {
+        let line = b"|C170|1|7894900011517|\r\n";
+        let mut data = line.repeat(3 * CHUNK_SIZE / line.len());
+        data.extend_from_slice(b"\n\nlast");
+
+        let mut scanner = Scanner::default();
+        scanner.feed(&data);
+        let sequential = scanner.finish();
+
+        assert_eq!(FileStats::from_bytes(&data), sequential);
+    }
+}
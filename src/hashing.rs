@@ -0,0 +1,247 @@
+//! # Parallel File Hashing
+//!
+//! Hashes files and directories with BLAKE3, in parallel with `rayon`.
+//!
+//! With the `fast-lines` feature, large files are memory-mapped and
+//! each file is also hashed with multiple threads (`Hasher::update_rayon`).
+
+use crate::{Error, Result, get_progressbar};
+use blake3::{Hash, Hasher};
+use rayon::prelude::*;
+use std::{
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "fast-lines")]
+use memmap2::Mmap;
+
+/// Files at least this large are memory-mapped and hashed with
+/// multiple threads when the `fast-lines` feature is enabled.
+pub const MMAP_THRESHOLD: u64 = 128 * 1024;
+
+/// Size of the read buffer used by [`hash_file`] for unmapped files.
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/**
+Hashes one file with BLAKE3, returning the hash and the file size.
+
+Example:
+```
+    use claudiofsr_lib::hash_file;
+
+    let path = "/tmp/hash_file_example.txt";
+    std::fs::write(path, "foo bar").unwrap();
+
+    let (hash, size) = hash_file(path).unwrap();
+    assert_eq!(hash, blake3::hash(b"foo bar"));
+    assert_eq!(size, 7);
+
+    std::fs::remove_file(path).unwrap();
+```
+*/
+pub fn hash_file<P>(path: P) -> io::Result<(Hash, u64)>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut hasher = Hasher::new();
+
+    #[cfg(feature = "fast-lines")]
+    if size >= MMAP_THRESHOLD {
+        // Safety: the file must not be modified while mapped (see FileExtension::count_lines).
+        let mmap = unsafe { Mmap::map(&file)? };
+        hasher.update_rayon(&mmap);
+        return Ok((hasher.finalize(), size));
+    }
+
+    let reader = io::BufReader::with_capacity(BUFFER_SIZE, file);
+    hasher.update_reader(reader)?;
+
+    Ok((hasher.finalize(), size))
+}
+
+/**
+Hashes a list of files in parallel.
+
+Returns `(path, hash, size)` for each file, in the same order as the input.
+
+If `show_progress` is true, an aggregated progress bar (by bytes) is displayed
+(see [`get_progressbar`]).
+
+### Errors
+Returns the first error found, with the path of the file.
+
+Example:
+```
+    use claudiofsr_lib::hash_files;
+
+    let paths = ["/tmp/hash_files_a.txt", "/tmp/hash_files_b.txt"];
+    std::fs::write(paths[0], "a").unwrap();
+    std::fs::write(paths[1], "bb").unwrap();
+
+    let results = hash_files(paths, false).unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[1].0.to_str(), Some(paths[1]));
+    assert_eq!(results[1].1, blake3::hash(b"bb"));
+    assert_eq!(results[1].2, 2);
+
+    paths.iter().for_each(|path| std::fs::remove_file(path).unwrap());
+```
+*/
+pub fn hash_files<I, P>(paths: I, show_progress: bool) -> Result<Vec<(PathBuf, Hash, u64)>>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    let paths: Vec<PathBuf> = paths
+        .into_iter()
+        .map(|path| path.as_ref().to_path_buf())
+        .collect();
+
+    let progressbar = if show_progress {
+        let total: u64 = paths
+            .iter()
+            .map(|path| match fs::metadata(path) {
+                Ok(metadata) => Ok(metadata.len()),
+                Err(error) => Err(Error::io(path, error)),
+            })
+            .sum::<Result<u64>>()?;
+        Some(get_progressbar("Hashing files", total.try_into()?)?)
+    } else {
+        None
+    };
+
+    let results = paths
+        .into_par_iter()
+        .map(|path| {
+            let (hash, size) = hash_file(&path).map_err(|error| Error::io(&path, error))?;
+            if let Some(pb) = &progressbar {
+                pb.inc(size);
+            }
+            Ok((path, hash, size))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if let Some(pb) = progressbar {
+        pb.finish();
+    }
+
+    Ok(results)
+}
+
+/// Hashes every file in a directory (recursively) in parallel.
+///
+/// Files are returned sorted by path. See [`hash_files`] and [`list_files`].
+pub fn hash_directory<P>(dir: P, show_progress: bool) -> Result<Vec<(PathBuf, Hash, u64)>>
+where
+    P: AsRef<Path>,
+{
+    let files = list_files(dir)?;
+    hash_files(files, show_progress)
+}
+
+/**
+Lists the regular files in a directory and its subdirectories, sorted by path.
+
+Symbolic links are not followed.
+
+Example:
+```
+    use claudiofsr_lib::list_files;
+    use std::path::PathBuf;
+
+    let dir = "/tmp/list_files_example";
+    std::fs::create_dir_all(format!("{dir}/sub")).unwrap();
+    std::fs::write(format!("{dir}/b.txt"), "b").unwrap();
+    std::fs::write(format!("{dir}/sub/a.txt"), "a").unwrap();
+
+    let files: Vec<PathBuf> = list_files(dir).unwrap();
+    assert_eq!(files, [
+        PathBuf::from(format!("{dir}/b.txt")),
+        PathBuf::from(format!("{dir}/sub/a.txt")),
+    ]);
+
+    std::fs::remove_dir_all(dir).unwrap();
+```
+*/
+pub fn list_files<P>(dir: P) -> Result<Vec<PathBuf>>
+where
+    P: AsRef<Path>,
+{
+    let mut files = Vec::new();
+    let mut pending = vec![dir.as_ref().to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir).map_err(|error| Error::io(&dir, error))?;
+        for entry in entries {
+            let entry = entry.map_err(|error| Error::io(&dir, error))?;
+            let file_type = entry
+                .file_type()
+                .map_err(|error| Error::io(entry.path(), error))?;
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output hashing_tests
+// cargo test --features fast-lines -- --show-output hashing_tests
+
+#[cfg(test)]
+mod hashing_tests {
+    use super::*;
+
+    #[test]
+    fn large_and_small_files() -> Result<()> {
+        let dir = "/tmp/hashing_tests";
+        fs::create_dir_all(dir)?;
+
+        let small = b"|0000|small|\n".to_vec();
+        let large: Vec<u8> = (0..MMAP_THRESHOLD * 3).map(|i| (i % 251) as u8).collect();
+        let empty: Vec<u8> = Vec::new();
+
+        for (name, data) in [("small", &small), ("large", &large), ("empty", &empty)] {
+            fs::write(format!("{dir}/{name}"), data)?;
+        }
+
+        let results = hash_directory(dir, false)?;
+        let names: Vec<_> = results
+            .iter()
+            .map(|(path, _, _)| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, ["empty", "large", "small"]);
+
+        for ((_, hash, size), data) in results.iter().zip([&empty, &large, &small]) {
+            assert_eq!(*hash, blake3::hash(data));
+            assert_eq!(*size, data.len() as u64);
+        }
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn missing_file_error_has_path() {
+        let error = hash_files(["/tmp/hashing_tests_missing.txt"], false).unwrap_err();
+        println!("error: {error}");
+        assert!(
+            error
+                .to_string()
+                .starts_with("/tmp/hashing_tests_missing.txt: ")
+        );
+    }
+}
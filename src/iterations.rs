@@ -1,12 +1,21 @@
-use std::{fs::File, io};
-
-#[cfg(not(feature = "fast-lines"))]
-use std::io::{BufReader, Read};
+use crate::{ENCODING_SAMPLE_SIZE, Encoding, FileStats, file_stats};
+use std::{
+    fs::File,
+    io::{self, Read},
+};
 
 // Importações condicionais apenas se a feature estiver ativa
 #[cfg(feature = "fast-lines")]
 use {memmap2::Mmap, rayon::prelude::*};
 
+/// Size of the blocks read by [`FileExtension::count_lines`] without `fast-lines`.
+#[cfg(not(feature = "fast-lines"))]
+const COUNT_LINES_BLOCK_SIZE: usize = 256 * 1024;
+
+/// Size of the chunks counted by each thread with `fast-lines`.
+#[cfg(feature = "fast-lines")]
+const COUNT_LINES_CHUNK_SIZE: usize = 1024 * 1024;
+
 /// Extension trait adding utility methods for file manipulation.
 pub trait FileExtension {
     /**
@@ -15,15 +24,19 @@ pub trait FileExtension {
     ### Behavior
     It strictly counts the newline byte `0x0A` (`\n`), similar to the unix `wc -l` command.
 
+    A final line without a trailing newline is **not** counted:
+    `"a\nb\n"` gives `2`, but `"a\nb"` gives `1`.
+    Both implementations return the same value for the same file.
+
     ### Characteristics:
     1. **Encoding-agnostic**: Works with UTF-8, ASCII, and legacy encodings (binary safe).
-    2. **Memory Efficient**: Uses `BufReader` for buffered reads and iterates continuously
-       without allocating memory for line strings (Zero-Allocation logic).
-    3. **Side Effects**: The file cursor **will remain at the end of the file**.
+    2. **Memory Efficient**: Reads large blocks and counts newlines a word at a time
+       (see [`count_newlines`]), without allocating memory for line strings.
+    3. **Side Effects**: Without `fast-lines`, the file cursor **will remain at the end of the file**.
        If you need to read the file again, you must manually `rewind` it.
 
     ### Implementations
-    1. **Standard (Default)**: Uses `BufReader` and `try_fold`. Zero-allocation and safe.
+    1. **Standard (Default)**: Reads 256 KiB blocks into a reusable buffer. Safe, no mmap.
     2. **Fast (Feature `fast-lines`)**: Uses `memmap2` and `rayon` for parallel processing.
        This creates a memory map of the file and counts newlines using multiple CPU threads.
        *Warning*: This involves `unsafe` code internally.
@@ -60,6 +73,67 @@ pub trait FileExtension {
     ```
     */
     fn count_lines(&self) -> io::Result<u64>;
+
+    /**
+    Detects the text encoding from the first bytes of the file.
+
+    Reads at most [`ENCODING_SAMPLE_SIZE`] bytes, see [`Encoding::detect_sample`].
+
+    The file cursor is advanced by the bytes read.
+
+    ### Example:
+    ```
+    use claudiofsr_lib::{Encoding, FileExtension};
+    use std::fs::File;
+
+    fn main() -> std::io::Result<()> {
+        let path = "/tmp/sample_latin1.txt";
+        std::fs::write(path, b"Relat\xF3rio\n")?;
+
+        let encoding = File::open(path)?.detect_encoding()?;
+        assert_eq!(encoding, Encoding::Latin1);
+
+        // A short file ending in an accented Latin-1 byte.
+        std::fs::write(path, b"caf\xE9")?;
+        assert_eq!(File::open(path)?.detect_encoding()?, Encoding::Latin1);
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+    ```
+    */
+    fn detect_encoding(&self) -> io::Result<Encoding>;
+
+    /**
+    Computes line statistics in a single pass: lines, bytes, longest line,
+    blank lines, line endings, BOM and final newline (see [`FileStats`]).
+
+    Uses the same strategy as [`FileExtension::count_lines`]: large blocks in the
+    default build, memory map and parallel chunks (split at line boundaries)
+    with the `fast-lines` feature.
+
+    Without `fast-lines`, the file cursor will remain at the end of the file.
+
+    ### Example:
+    ```
+    use claudiofsr_lib::{FileExtension, LineEnding};
+    use std::fs::File;
+
+    fn main() -> std::io::Result<()> {
+        let path = "/tmp/sample_stats.txt";
+        std::fs::write(path, "|0000|\r\n|9999|\n")?;
+
+        let stats = File::open(path)?.file_stats()?;
+        assert_eq!(stats.lines, 2);
+        assert_eq!(stats.line_ending(), LineEnding::Mixed);
+        assert!(stats.last_line_terminated);
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+    ```
+    */
+    fn file_stats(&self) -> io::Result<FileStats>;
 }
 
 // cargo test -- --show-output count_lines
@@ -76,12 +150,14 @@ impl FileExtension for File {
         // However, for a cli utility reading a file, this is usually an acceptable risk.
         let mmap = unsafe { Mmap::map(self)? };
 
-        // Rayon parallel iterator: splits the byte slice across threads
-        // and counts occurrences of '\n' extremely fast.
-        // We use `filter` instead of `split` to avoid semantic ambiguity and allocations.
-        let count = mmap.par_iter().filter(|&&byte| byte == b'\n').count();
+        // Rayon parallel iterator: splits the byte slice into chunks across threads,
+        // and each chunk is counted with the same scanner as the standard implementation.
+        let count = mmap
+            .par_chunks(COUNT_LINES_CHUNK_SIZE)
+            .map(count_newlines)
+            .sum();
 
-        Ok(count as u64)
+        Ok(count)
     }
 
     // -------------------------------------------------------------------------
@@ -89,19 +165,70 @@ impl FileExtension for File {
     // -------------------------------------------------------------------------
     #[cfg(not(feature = "fast-lines"))]
     fn count_lines(&self) -> io::Result<u64> {
-        // Wrap the file in a BufReader for efficient I/O (reduces syscalls).
         // Using a reference to self ensures we don't consume the File ownership.
-        BufReader::new(self)
-            .bytes() // Returns an iterator of Result<u8, io::Error>
-            // try_fold is the functional equivalent of a reduce loop with error propagation.
-            .try_fold(0u64, |acc, byte_result| {
-                // Map the result: if Ok(byte), check for newline.
-                // If Err, it propagates immediately.
-                byte_result.map(|b| if b == b'\n' { acc + 1 } else { acc })
-            })
+        let mut reader: &File = self;
+        let mut buffer = vec![0u8; COUNT_LINES_BLOCK_SIZE];
+        let mut count = 0;
+
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => return Ok(count),
+                Ok(n) => count += count_newlines(&buffer[..n]),
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    fn file_stats(&self) -> io::Result<FileStats> {
+        file_stats(self)
+    }
+
+    fn detect_encoding(&self) -> io::Result<Encoding> {
+        let mut sample = Vec::new();
+        self.take(ENCODING_SAMPLE_SIZE).read_to_end(&mut sample)?;
+        let at_eof = (sample.len() as u64) < ENCODING_SAMPLE_SIZE;
+        Ok(Encoding::detect_sample(&sample, at_eof))
     }
 }
 
+/**
+Counts the newline bytes (`\n`) in a slice.
+
+Processes 8 bytes at a time (SWAR: SIMD within a register), which lets the
+compiler vectorize the loop. Used by both implementations of
+[`FileExtension::count_lines`].
+
+Example:
+```
+    use claudiofsr_lib::count_newlines;
+
+    assert_eq!(count_newlines(b"|0000|\n|0001|0|\n|9999|5|"), 2);
+    assert_eq!(count_newlines(b""), 0);
+```
+*/
+pub fn count_newlines(bytes: &[u8]) -> u64 {
+    const LOW_BITS: u64 = u64::from_ne_bytes([0x7F; 8]);
+    const NEWLINES: u64 = u64::from_ne_bytes([b'\n'; 8]);
+
+    let words = bytes.chunks_exact(8);
+    let tail = words.remainder();
+
+    let count: u64 = words
+        .map(|word| {
+            let word = u64::from_ne_bytes(word.try_into().unwrap());
+            // Bytes equal to '\n' become zero.
+            let x = word ^ NEWLINES;
+            // The high bit of each byte is set only if that byte of `x` is zero
+            // (no carries between bytes, so there are no false positives).
+            let zeros = !(((x & LOW_BITS) + LOW_BITS) | x | LOW_BITS);
+            u64::from(zeros.count_ones())
+        })
+        .sum();
+
+    count + tail.iter().filter(|&&byte| byte == b'\n').count() as u64
+}
+
 /**
 The `IteratorBack` trait provides extension methods for iterators
 to skip a specified number of elements from their end.
@@ -371,4 +498,60 @@ mod count_lines_tests {
         std::fs::remove_file(path)?;
         Ok(())
     }
+
+    #[test]
+    fn test_last_line_without_newline() -> io::Result<()> {
+        let path = "/tmp/sample_lines_unterminated.txt";
+
+        for (content, expected) in [
+            ("", 0),
+            ("no newline", 0),
+            ("\n", 1),
+            ("Line 1\nLine 2", 1),
+            ("Line 1\nLine 2\n", 2),
+            ("\r\n\r\n", 2),
+        ] {
+            std::fs::write(path, content)?;
+            let number_of_lines = File::open(path)?.count_lines()?;
+            assert_eq!(number_of_lines, expected, "content: {content:?}");
+        }
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_newlines_matches_naive_count() {
+        // Every alignment and every position of '\n' within a word.
+        let data: Vec<u8> = (0..1000u32)
+            .map(|i| match i % 7 {
+                0 => b'\n',
+                1 => 0x8A, // '\n' with the high bit set
+                2 => 0x0B,
+                3 => 0x09,
+                _ => (i % 256) as u8,
+            })
+            .collect();
+
+        for start in 0..16 {
+            let slice = &data[start..];
+            let naive = slice.iter().filter(|&&b| b == b'\n').count() as u64;
+            assert_eq!(count_newlines(slice), naive);
+        }
+
+        assert_eq!(count_newlines(&[b'\n'; 64]), 64);
+        assert_eq!(count_newlines(&[0u8; 64]), 0);
+    }
+
+    #[test]
+    fn test_count_lines_large_file() -> io::Result<()> {
+        let path = "/tmp/sample_lines_large.txt";
+        let line = "|C170|1|7894900011517|PRODUTO|10|UN|125,50|\n";
+        std::fs::write(path, line.repeat(50_000))?;
+
+        assert_eq!(File::open(path)?.count_lines()?, 50_000);
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
 }
@@ -1,61 +1,68 @@
-use blake3::Hasher as Blake3Hasher;
 use chrono::NaiveDate;
-use indicatif::{ProgressBar, ProgressStyle};
 
 use std::{
     collections::{HashMap, HashSet},
     fs::{self, File},
-    io::{BufReader, Read, Write},
+    io::Write,
     ops::Deref,
     path::{self, Path},
-    process::Command,
-    str,
 };
 
+mod ansi;
+mod atomic;
+mod bytes;
+mod codecs;
 mod constants;
+mod content_id;
 mod count_digits;
+mod duplicates;
+mod encoding;
+mod error;
+mod file_stats;
+mod hashing;
 mod iterations;
 mod macros;
+mod manifest;
 mod maps;
 mod operations;
 mod options;
+mod par_lines;
+mod progress;
+mod progress_iter;
+mod prompt;
 mod random;
+mod read_lines;
+mod reverse_lines;
 mod rounded;
 mod separator;
 mod slice;
+mod sped;
 mod strings;
+mod style;
+mod table;
+mod table_export;
+mod tee;
+mod terminal;
 mod traits;
 mod unique;
+mod write_lines;
 
 pub use self::{
-    constants::*, count_digits::*, iterations::*, macros::*, maps::*, operations::*, options::*,
-    random::*, rounded::*, separator::*, slice::*, strings::*, traits::*, unique::*,
+    ansi::*, atomic::*, bytes::*, codecs::*, constants::*, content_id::*, count_digits::*,
+    duplicates::*, encoding::*, error::*, file_stats::*, hashing::*, iterations::*, macros::*,
+    manifest::*, maps::*, operations::*, options::*, par_lines::*, progress::*, progress_iter::*,
+    prompt::*, random::*, read_lines::*, reverse_lines::*, rounded::*, separator::*, slice::*,
+    sped::*, strings::*, style::*, table::*, table_export::*, tee::*, terminal::*, traits::*,
+    unique::*, write_lines::*,
 };
 
+/// Boxed error, for programs that mix the errors of this crate with others.
+///
+/// The functions of this crate return [`Error`] (see [`Result`]),
+/// which converts to `MyError` with `?`.
 pub type MyError = Box<dyn std::error::Error + Send + Sync + 'static>;
 pub type MyResult<T> = Result<T, MyError>;
 
-const HEX: [char; 16] = [
-    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f',
-];
-
-/// Clear (wipe) the terminal screen
-pub fn clear_terminal_screen() {
-    let result = if cfg!(target_os = "windows") {
-        Command::new("cmd").args(["/c", "cls"]).spawn()
-    } else {
-        // "clear" or "tput reset"
-        Command::new("tput").arg("reset").spawn()
-    };
-
-    // Alternative solution:
-    if result.is_err()
-        && let Some(esc) = char::from_digit(27, 10)
-    {
-        print!("{esc}c");
-    }
-}
-
 // https://stackoverflow.com/questions/56620265/how-to-access-the-bufreader-twice/
 
 /// Opens a file at the specified path.
@@ -75,93 +82,6 @@ where
         .open(path)
 }
 
-/// Byte slice `&[u8]` extension.
-pub trait BytesExtension {
-    /**
-    Trim ascii whitespace from the start and end of `&[u8]`.
-
-    Returns `&[u8]` with leading and trailing whitespace removed.
-
-    Example:
-    ```
-        use claudiofsr_lib::BytesExtension;
-
-        let text1: &str = " foo bar\r\n";
-        let text2: &str = "foo\nbar";
-        let text3: &str = "";
-
-        let bytes1: Vec<u8> = text1.bytes().collect();
-        let bytes2: Vec<u8> = text2.bytes().collect();
-        let bytes3: Vec<u8> = text3.bytes().collect();
-
-        println!("bytes1: {bytes1:?}");
-        println!("bytes2: {bytes2:?}");
-        println!("bytes3: {bytes3:?}");
-
-        let trimmed1: &[u8] = bytes1.trim();
-        let trimmed2: &[u8] = bytes2.trim();
-        let trimmed3: &[u8] = bytes3.trim();
-
-        println!("trimmed1: {trimmed1:?}");
-        println!("trimmed2: {trimmed2:?}");
-        println!("trimmed3: {trimmed3:?}");
-
-        assert_eq!(bytes1, [32, 102, 111, 111, 32, 98, 97, 114, 13, 10]);
-        assert_eq!(bytes2, [102, 111, 111, 10, 98, 97, 114]);
-        assert!(bytes3.is_empty());
-
-        assert_eq!(trimmed1, [102, 111, 111, 32, 98, 97, 114]);
-        assert_eq!(trimmed2, [102, 111, 111, 10, 98, 97, 114]);
-        assert_eq!(trimmed3, []);
-    ```
-    <https://stackoverflow.com/questions/31101915/how-to-implement-trim-for-vecu8>
-    */
-    fn trim(&self) -> &Self;
-
-    /**
-    `&[u8]` to  hex string
-
-    Example:
-    ```
-        use claudiofsr_lib::BytesExtension;
-
-        let text: &str = " foo bar\n";
-        let bytes: Vec<u8> = text.bytes().collect();
-
-        println!("bytes: {bytes:?}");
-
-        let string = bytes.to_hex_string();
-
-        println!("string: {string:?}");
-
-        assert_eq!(bytes, [32, 102, 111, 111, 32, 98, 97, 114, 10]);
-        assert_eq!(string, "20666f6f206261720a");
-    ```
-    */
-    fn to_hex_string(&self) -> String;
-}
-
-impl BytesExtension for [u8] {
-    fn trim(&self) -> &[u8] {
-        let from = match self.iter().position(|b| !b.is_ascii_whitespace()) {
-            Some(index) => index,
-            None => return &self[..0],
-        };
-        let to = self.iter().rposition(|b| !b.is_ascii_whitespace()).unwrap();
-        &self[from..=to]
-    }
-
-    fn to_hex_string(&self) -> String {
-        self.iter()
-            .flat_map(|byte| {
-                let a: char = HEX[(*byte as usize) / 16];
-                let b: char = HEX[(*byte as usize) % 16];
-                vec![a, b]
-            })
-            .collect()
-    }
-}
-
 /**
 Convert Vec\<&str\> to Vec\<String\>
 
@@ -212,13 +132,46 @@ where
     v.iter().map(|x| x.as_ref()).collect()
 }
 
+/// Parses a date from a string containing at least 8 digits.
+///
+/// Date format: DDMMYYYY (non-digits are ignored, extra digits after
+/// the first 8 are ignored).
+///
+/// ### Errors
+/// Returns [`Error::Parse`] if there are fewer than 8 digits,
+/// or if the date is out of range or does not exist.
+///
+/// Example:
+/// ```
+///     use claudiofsr_lib::{Error, parse_naive_date};
+///     use chrono::NaiveDate;
+///
+///     let date = parse_naive_date("06-12-2022T00:00:00-03:00").unwrap();
+///     assert_eq!(Some(date), NaiveDate::from_ymd_opt(2022, 12, 6));
+///
+///     let error = parse_naive_date("29021973").unwrap_err();
+///     assert!(matches!(error, Error::Parse { .. }));
+///     assert_eq!(error.to_string(), "input is out of range: \"29021973\"");
+/// ```
+pub fn parse_naive_date(date: &str) -> Result<NaiveDate> {
+    // "06-12-2022T00:00:00-03:00" -> "061220220000000300"
+    let digits: String = date.remove_non_digits();
+
+    let ddmmyyyy: &str = digits
+        .get(..8)
+        .ok_or_else(|| Error::parse(date, None, "expected a date with 8 digits (DDMMYYYY)"))?;
+
+    NaiveDate::parse_from_str(ddmmyyyy, "%-d%-m%Y").map_err(|why| Error::parse(date, None, why))
+}
+
 /// Gets Date from a string containing 8 digits.
 ///
 /// Date format: DDMMYYYY.
 ///
 /// Check if NaiveDate is valid.
 ///
-/// Returns None on the out-of-range date, invalid month and/or day.
+/// Returns None on the out-of-range date, invalid month and/or day
+/// (see [`parse_naive_date`] for the error).
 ///
 /// <https://docs.rs/chrono/latest/chrono/naive/struct.NaiveDate.html#method.from_ymd_opt>
 ///
@@ -242,44 +195,20 @@ where
 /// ```
 pub fn get_naive_date<T>(date: T) -> Option<NaiveDate>
 where
-    T: Deref<Target = str> + std::fmt::Display,
+    T: Deref<Target = str>,
 {
-    // "06-12-2022T00:00:00-03:00" -> "061220220000000300"
-    let digits: String = date.remove_non_digits();
-
-    let ddmmyyyy: &str = if digits.chars_count() >= 8 {
-        &digits[..8]
-    } else {
-        return None;
-    };
-
-    match NaiveDate::parse_from_str(ddmmyyyy, "%-d%-m%Y") {
-        Ok(dt) => Some(dt),
-        Err(why) => {
-            eprintln!("fn get_naive_date()");
-            eprintln!("Data inválida ou inexistente!");
-            eprintln!("Erro: {why}");
-            eprintln!("\t'{date}'");
-            None
-        }
-    }
+    parse_naive_date(&date).ok()
 }
 
 /// Gets Date from a string containing 8 digits.
 pub fn get_naive_date_v2<T>(date: T) -> Option<NaiveDate>
 where
-    T: Deref<Target = str> + std::fmt::Display,
+    T: Deref<Target = str>,
 {
     let digits: String = date.remove_non_digits();
 
     // date: DDMMYYYY
-    let ddmmyyyy: u32 = if digits.chars_count() >= 8 {
-        digits[..8]
-            .parse::<u32>()
-            .expect("fn get_naive_date()\nEsperado um número inteiro com 8 dígitos!")
-    } else {
-        return None;
-    };
+    let ddmmyyyy: u32 = digits.get(..8)?.parse::<u32>().ok()?;
 
     let day = ddmmyyyy / 1_000_000;
     let mmyyyy = ddmmyyyy % 1_000_000;
@@ -287,14 +216,7 @@ where
     let month = mmyyyy / 10_000;
     let year = mmyyyy % 10_000;
 
-    let dt: Option<NaiveDate> = NaiveDate::from_ymd_opt(year as i32, month, day);
-
-    if dt.is_none() {
-        eprintln!("Erro! Data inválida ou inexistente:");
-        eprintln!("\t'{date}': day: {day} ; month: {month} ; year: {year}");
-    }
-
-    dt
+    NaiveDate::from_ymd_opt(year as i32, month, day)
 }
 
 // https://stackoverflow.com/questions/26536871/how-can-i-convert-a-string-of-numbers-to-an-array-or-vector-of-integers-in-rust
@@ -313,110 +235,60 @@ where
 ///     assert_eq!(vector1, vector2);
 ///     assert_eq!(vector1, vec![6, 12, 2022]);
 /// ```
-pub fn string_to_vec_of_integers<T>(string: T) -> MyResult<Vec<u32>>
+///
+/// ### Errors
+/// Returns [`Error::Parse`] with the first invalid number and its
+/// position (in bytes) in `string`.
+pub fn string_to_vec_of_integers<T>(string: T) -> Result<Vec<u32>>
 where
     T: Deref<Target = str>,
 {
-    let vec_u32: Result<Vec<u32>, _> = string // Error: ParseIntError
+    string
         .split_whitespace()
-        .map(|s: &str| s.parse::<u32>())
-        .collect();
-
-    Ok(vec_u32?)
-}
-
-/// Command line progress with indicatif ProgressBar
-pub fn get_progressbar(msg: &'static str, total: usize) -> MyResult<ProgressBar> {
-    let style = get_style(0, 0, 38)?;
-
-    let pb = ProgressBar::new(total.try_into()?);
-    pb.set_message(msg);
-    pb.set_style(style);
-
-    Ok(pb)
-}
-
-/// Genarate ProgressStyle by template and progress characters.
-pub fn get_style(
-    template_index: usize,
-    chars_index: usize,
-    length: usize,
-) -> MyResult<ProgressStyle> {
-    let template_01 = format!(
-        "{{msg:{length}}} {{spinner:.green}} [{{wide_bar:.cyan/blue}}] {{percent}}/100% ({{eta}}) [{{elapsed_precise}}]"
-    );
-    let template_02 = format!(
-        "{{msg:{length}}} {{spinner:.green}} [{{wide_bar:.cyan/blue}}] {{percent}}/100% ({{eta}})"
-    );
-    let template_03 = format!(
-        "{{msg:{length}}} {{spinner:.green}} [{{wide_bar:.cyan/blue}}] {{pos}}/{{len}} ({{eta}})"
-    );
-    let template_04 =
-        format!("[{{elapsed_precise}}] {{bar:40.cyan/blue}} {{pos}}/{{len}} {{msg:{length}}}");
-
-    let templates = [template_01, template_02, template_03, template_04];
-
-    let progress_characters = ["#>-", "## ", "■□ ", "█░-", "🦀👾👻"];
-
-    let style: ProgressStyle = ProgressStyle::default_bar()
-        .template(&templates[template_index])?
-        .progress_chars(progress_characters[chars_index]);
-
-    Ok(style)
+        .map(|s: &str| {
+            s.parse::<u32>().map_err(|why| {
+                // `s` is a subslice of `string`.
+                let position = s.as_ptr() as usize - string.as_ptr() as usize;
+                Error::parse(s, Some(position), why)
+            })
+        })
+        .collect()
 }
 
 /// Print to file and to stdout
-pub fn my_print<P>(write_buffer: &[u8], path: P) -> MyResult<()>
+///
+/// The file is replaced atomically (see [`AtomicFile`]).
+///
+/// Invalid UTF-8 sequences are written unchanged to the file
+/// and printed to stdout as `U+FFFD` (see [`Tee`]).
+pub fn my_print<P>(write_buffer: &[u8], path: P) -> Result<()>
 where
     P: AsRef<path::Path>,
 {
-    // Print to file
-    let mut file = fs::File::create(path)?;
-    file.write_all(write_buffer)?;
-
-    // Converts a slice of bytes to a string slice
-    let print_msg = match str::from_utf8(write_buffer) {
-        Ok(valid_uft8) => valid_uft8,
-        Err(error) => {
-            eprintln!("fn my_print()");
-            eprintln!("Invalid UTF-8 sequence!");
-            panic!("{error}");
-        }
-    };
+    let mut tee = Tee::new().with_file(path)?.with_stdout();
+    tee.write_all(write_buffer)?;
+    tee.finish()?;
 
-    // Print to stdout
-    // writeln!(std::io::stdout(), "{print_msg}")?;
-    println!("{print_msg}");
+    // Print the final newline to stdout only
+    println!();
 
     Ok(())
 }
 
 /// Calculates the Blake3 hash from Path.
 ///
+/// See [`hash_files`] to hash many files in parallel.
+///
 /// <https://docs.rs/blake3/latest/blake3>
 ///
 /// <https://rust-lang-nursery.github.io/rust-cookbook/cryptography/hashing.html>
-pub fn blake3_hash<P>(path: P) -> MyResult<String>
+pub fn blake3_hash<P>(path: P) -> Result<String>
 where
     P: AsRef<Path> + std::marker::Copy + std::fmt::Debug,
 {
-    let file: File = open_file(path)?;
-    let mut reader: BufReader<File> = BufReader::new(file);
-    let mut buffer = [0; 1024];
-
-    let mut hasher = Blake3Hasher::new();
-
-    loop {
-        let count = reader.read(&mut buffer)?;
-        if count == 0 {
-            break;
-        }
-        hasher.update(&buffer[..count]);
-    }
-
-    let hash: String = hasher.finalize().to_string();
+    let (hash, _size) = hash_file(path)?;
 
-    Ok(hash)
+    Ok(hash.to_string())
 }
 
 /// Split a slice into smaller slices of size N.
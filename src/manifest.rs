@@ -0,0 +1,451 @@
+//! # Checksum Manifests
+//!
+//! Writes and verifies BLAKE3 manifests in the `b3sum` text format:
+//!
+//! ```text
+//! <64 hex digits><two spaces><path>
+//! ```
+//!
+//! As in `b3sum`, a path containing `\`, a line feed or a carriage return is escaped
+//! (`\\`, `\n` and `\r`) and the line starts with a single `\`.
+//!
+//! A manifest written here can be checked with `b3sum --check`, and vice versa.
+
+use crate::{AtomicFile, Error, ReadLinesError, Result, hash_file, hash_files};
+use blake3::Hash;
+use rayon::prelude::*;
+use std::{
+    fmt::{self, Display},
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+};
+
+/// A line of a checksum manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// The expected BLAKE3 hash.
+    pub hash: Hash,
+    /// The file path, as written in the manifest.
+    pub path: PathBuf,
+}
+
+impl ManifestEntry {
+    /**
+    Formats the entry as a `b3sum` line (without the line break).
+
+    ### Errors
+    Returns an error if the path is not valid UTF-8.
+
+    Example:
+    ```
+        use claudiofsr_lib::ManifestEntry;
+
+        let hash = blake3::hash(b"");
+        let entry = ManifestEntry { hash, path: "dir\\new\nfile.txt".into() };
+
+        assert_eq!(
+            entry.to_line().unwrap(),
+            format!("\\{hash}  dir\\\\new\\nfile.txt")
+        );
+    ```
+    */
+    pub fn to_line(&self) -> Result<String> {
+        let path = self
+            .path
+            .to_str()
+            .ok_or_else(|| Error::Encoding(format!("path is not valid UTF-8: {:?}", self.path)))?;
+
+        if path.contains(['\\', '\n', '\r']) {
+            let escaped = path
+                .replace('\\', "\\\\")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r");
+            Ok(format!("\\{}  {escaped}", self.hash))
+        } else {
+            Ok(format!("{}  {path}", self.hash))
+        }
+    }
+
+    /// Parses a `b3sum` line (without the line break).
+    pub fn from_line(line: &str) -> Result<Self, String> {
+        let (escaped, line) = match line.strip_prefix('\\') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+
+        let (hex, path) = line
+            .split_at_checked(64)
+            .ok_or("line too short for a BLAKE3 hash")?;
+
+        let hash = Hash::from_hex(hex).map_err(|error| error.to_string())?;
+
+        let path = path
+            .strip_prefix("  ")
+            .filter(|path| !path.is_empty())
+            .ok_or("expected two spaces and a path after the hash")?;
+
+        let path = if escaped {
+            unescape(path)?
+        } else {
+            path.to_string()
+        };
+
+        Ok(ManifestEntry {
+            hash,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+/// Reverts the `b3sum` path escaping.
+fn unescape(path: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(path.len());
+    let mut chars = path.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('\\') => result.push('\\'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            other => {
+                return Err(format!(
+                    "invalid escape sequence in path: \\{}",
+                    other.unwrap_or(' ').escape_default()
+                ));
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/**
+Hashes the files in parallel and writes a `b3sum` manifest to `writer`,
+in the same order as the input.
+
+Returns the number of lines written.
+
+See [`hash_files`] for `show_progress`.
+*/
+pub fn write_manifest<I, P, W>(paths: I, mut writer: W, show_progress: bool) -> Result<usize>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+    W: Write,
+{
+    let results = hash_files(paths, show_progress)?;
+
+    for (path, hash, _size) in &results {
+        let entry = ManifestEntry {
+            hash: *hash,
+            path: path.clone(),
+        };
+        writeln!(writer, "{}", entry.to_line()?)?;
+    }
+
+    writer.flush()?;
+    Ok(results.len())
+}
+
+/**
+Hashes the files in parallel and writes a `b3sum` manifest file.
+
+The manifest is replaced atomically (see [`AtomicFile`]).
+
+Example:
+```
+    use claudiofsr_lib::{CheckStatus, write_manifest_file, verify_manifest};
+
+    let files = ["/tmp/manifest_example_a.xml", "/tmp/manifest_example_b.xml"];
+    let manifest = "/tmp/manifest_example.b3";
+
+    std::fs::write(files[0], "<nfe>1</nfe>").unwrap();
+    std::fs::write(files[1], "<nfe>2</nfe>").unwrap();
+    write_manifest_file(files, manifest, false).unwrap();
+
+    let report = verify_manifest(manifest).unwrap();
+    assert!(report.is_success());
+    assert_eq!(report.exit_code(), 0);
+
+    // Modify one file and remove the other.
+    std::fs::write(files[0], "<nfe>3</nfe>").unwrap();
+    std::fs::remove_file(files[1]).unwrap();
+
+    let report = verify_manifest(manifest).unwrap();
+    assert_eq!(report.results[0].status, CheckStatus::Failed);
+    assert_eq!(report.results[1].status, CheckStatus::Missing);
+    assert_eq!(report.exit_code(), 1);
+    assert_eq!(
+        report.to_string(),
+        "/tmp/manifest_example_a.xml: FAILED\n/tmp/manifest_example_b.xml: MISSING\n"
+    );
+
+    std::fs::remove_file(files[0]).unwrap();
+    std::fs::remove_file(manifest).unwrap();
+```
+*/
+pub fn write_manifest_file<I, P, Q>(paths: I, manifest: Q, show_progress: bool) -> Result<usize>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let mut file = AtomicFile::create(manifest)?;
+    let count = write_manifest(paths, &mut file, show_progress)?;
+    file.commit()?;
+    Ok(count)
+}
+
+/**
+Reads the entries of a `b3sum` manifest.
+
+### Errors
+Returns an I/O error, or a [`ReadLinesError`] for the first invalid line.
+*/
+pub fn read_manifest<P>(manifest: P) -> Result<Vec<ManifestEntry>>
+where
+    P: AsRef<Path>,
+{
+    let manifest = manifest.as_ref();
+    let file = File::open(manifest).map_err(|error| Error::io(manifest, error))?;
+
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let line = line?;
+            ManifestEntry::from_line(&line).map_err(|message| {
+                ReadLinesError {
+                    path: manifest.to_path_buf(),
+                    line_number: index + 1,
+                    line,
+                    message,
+                }
+                .into()
+            })
+        })
+        .collect()
+}
+
+/// Result of checking one file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CheckStatus {
+    /// The file matches the expected hash.
+    Ok,
+    /// The file exists but its content differs.
+    Failed,
+    /// The file was not found.
+    Missing,
+}
+
+impl Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            CheckStatus::Ok => "OK",
+            CheckStatus::Failed => "FAILED",
+            CheckStatus::Missing => "MISSING",
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// The status of one file listed in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    /// The file path, as written in the manifest.
+    pub path: PathBuf,
+    /// The verification result.
+    pub status: CheckStatus,
+}
+
+/// Result of [`verify_manifest`], in manifest order.
+///
+/// `Display` prints one `<path>: <STATUS>` line per file, like `b3sum --check`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// One result per manifest entry.
+    pub results: Vec<CheckResult>,
+}
+
+impl VerifyReport {
+    /// Number of files with the given status.
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.status == status)
+            .count()
+    }
+
+    /// Returns true if every file matches its hash.
+    pub fn is_success(&self) -> bool {
+        self.results
+            .iter()
+            .all(|result| result.status == CheckStatus::Ok)
+    }
+
+    /**
+    Exit status for scripts, as in `b3sum --check`:
+
+    - `0`: every file is OK;
+    - `1`: at least one file is FAILED or MISSING.
+    */
+    pub fn exit_code(&self) -> i32 {
+        if self.is_success() { 0 } else { 1 }
+    }
+}
+
+impl Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.results {
+            writeln!(f, "{}: {}", result.path.display(), result.status)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+Verifies every entry in parallel.
+
+Relative paths are resolved from the current directory, as in `b3sum --check`.
+
+### Errors
+A missing file is reported as [`CheckStatus::Missing`]; any other I/O error
+(such as permission denied) is returned, with the path of the file.
+*/
+pub fn verify_entries(entries: &[ManifestEntry]) -> Result<VerifyReport> {
+    let results = entries
+        .par_iter()
+        .map(|entry| {
+            let status = match hash_file(&entry.path) {
+                Ok((hash, _size)) if hash == entry.hash => CheckStatus::Ok,
+                Ok(_) => CheckStatus::Failed,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => CheckStatus::Missing,
+                Err(error) => return Err(Error::io(&entry.path, error)),
+            };
+            Ok(CheckResult {
+                path: entry.path.clone(),
+                status,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(VerifyReport { results })
+}
+
+/// Reads a `b3sum` manifest and verifies every file listed.
+///
+/// See [`read_manifest`] and [`verify_entries`].
+pub fn verify_manifest<P>(manifest: P) -> Result<VerifyReport>
+where
+    P: AsRef<Path>,
+{
+    let entries = read_manifest(manifest)?;
+    verify_entries(&entries)
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output manifest_tests
+
+#[cfg(test)]
+mod manifest_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn escaped_paths_round_trip() -> Result<()> {
+        let hash = blake3::hash(b"SPED");
+
+        for path in [
+            "plain name.txt",
+            "back\\slash",
+            "line\nbreak",
+            "carriage\rreturn",
+            "windows\r\nbreak",
+            "\\n literal",
+        ] {
+            let entry = ManifestEntry {
+                hash,
+                path: path.into(),
+            };
+            let line = entry.to_line()?;
+            println!("line: {line:?}");
+            assert!(!line.contains(['\n', '\r']));
+            assert_eq!(ManifestEntry::from_line(&line)?, entry);
+        }
+
+        // Through a manifest file.
+        let manifest = "/tmp/manifest_tests_escaped.b3";
+        let entry = ManifestEntry {
+            hash,
+            path: "trailing\r".into(),
+        };
+        fs::write(manifest, format!("{}\n", entry.to_line()?))?;
+        assert_eq!(read_manifest(manifest)?, [entry]);
+
+        fs::remove_file(manifest)?;
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_lines() {
+        let hash = blake3::hash(b"").to_string();
+
+        for line in [
+            "",
+            "abc  file.txt",
+            &format!("{hash} file.txt"),
+            &format!("{hash}  "),
+            &format!("\\{hash}  bad\\escape"),
+        ] {
+            let result = ManifestEntry::from_line(line);
+            println!("{line:?}: {result:?}");
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn manifest_file_reports_line_number() -> Result<()> {
+        let manifest = "/tmp/manifest_tests_invalid.b3";
+        let hash = blake3::hash(b"");
+        fs::write(manifest, format!("{hash}  a.txt\nnot a hash\n"))?;
+
+        let error = read_manifest(manifest).unwrap_err();
+        let error = error.downcast_ref::<ReadLinesError>().unwrap();
+        assert_eq!(error.line_number, 2);
+        assert_eq!(error.line, "not a hash");
+
+        fs::remove_file(manifest)?;
+        Ok(())
+    }
+
+    #[test]
+    fn write_to_buffer_in_input_order() -> Result<()> {
+        let dir = "/tmp/manifest_tests_order";
+        fs::create_dir_all(dir)?;
+        fs::write(format!("{dir}/b.txt"), "b")?;
+        fs::write(format!("{dir}/a.txt"), "a")?;
+
+        let paths = [format!("{dir}/b.txt"), format!("{dir}/a.txt")];
+        let mut buffer = Vec::new();
+        let count = write_manifest(&paths, &mut buffer, false)?;
+
+        let expected = format!(
+            "{}  {dir}/b.txt\n{}  {dir}/a.txt\n",
+            blake3::hash(b"b"),
+            blake3::hash(b"a")
+        );
+        assert_eq!(count, 2);
+        assert_eq!(String::from_utf8(buffer)?, expected);
+
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
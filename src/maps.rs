@@ -1,9 +1,7 @@
-use crate::MyResult;
+use crate::{Result, WriteLines, WriteOptions};
 use itertools::Itertools;
 use std::{
     collections::{BTreeSet, HashSet},
-    fs::File,
-    io::Write,
     path::Path,
 };
 
@@ -72,7 +70,11 @@ pub trait HashSetExtension<T> {
         T: Ord;
 
     /// Write all HashSet elements to an output file.
-    fn write_to_file<P>(&self, output_file: P) -> MyResult<()>
+    ///
+    /// The file is replaced atomically and is not created for an empty set.
+    ///
+    /// See [`WriteLines`] for more options.
+    fn write_to_file<P>(&self, output_file: P) -> Result<()>
     where
         P: AsRef<Path>;
 }
@@ -92,26 +94,13 @@ where
         self.iter().sorted().cloned().collect()
     }
 
-    fn write_to_file<P>(&self, output_file: P) -> MyResult<()>
+    fn write_to_file<P>(&self, output_file: P) -> Result<()>
     where
         P: AsRef<Path>,
     {
-        // If the set is empty, return Ok() immediately
-        if self.is_empty() {
-            return Ok(());
-        }
-
-        // Create a new file with the given path
-        let mut file = File::create(output_file)?;
-
-        // Iterate over each element in the BTreeSet
-        for elem in self {
-            // Write each element to the file, followed by a newline character
-            writeln!(file, "{elem}")?;
-        }
-
-        // Return Ok() to indicate that writing was successful
-        Ok(())
+        // An empty set does not create the file
+        let options = WriteOptions::default().create_if_empty(false);
+        self.write_lines(output_file, &options)
     }
 }
 
@@ -146,7 +135,11 @@ pub trait BTreeSetExtension<T> {
     fn to_vec(&self) -> Vec<T>;
 
     /// Write all BTreeSet elements to an output file.
-    fn write_to_file<P>(&self, output_file: P) -> MyResult<()>
+    ///
+    /// The file is replaced atomically and is not created for an empty set.
+    ///
+    /// See [`WriteLines`] for more options.
+    fn write_to_file<P>(&self, output_file: P) -> Result<()>
     where
         P: AsRef<Path>;
 }
@@ -159,25 +152,12 @@ where
         self.iter().cloned().collect()
     }
 
-    fn write_to_file<P>(&self, output_file: P) -> MyResult<()>
+    fn write_to_file<P>(&self, output_file: P) -> Result<()>
     where
         P: AsRef<Path>,
     {
-        // If the set is empty, return Ok() immediately
-        if self.is_empty() {
-            return Ok(());
-        }
-
-        // Create a new file with the given path
-        let mut file = File::create(output_file)?;
-
-        // Iterate over each element in the BTreeSet
-        for elem in self {
-            // Write each element to the file, followed by a newline character
-            writeln!(file, "{elem}")?;
-        }
-
-        // Return Ok() to indicate that writing was successful
-        Ok(())
+        // An empty set does not create the file
+        let options = WriteOptions::default().create_if_empty(false);
+        self.write_lines(output_file, &options)
     }
 }
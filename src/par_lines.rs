@@ -0,0 +1,416 @@
+//! # Parallel Line Processing
+//!
+//! Processes the lines of a large file in parallel with `rayon`,
+//! returning the results in file order.
+//!
+//! With the `fast-lines` feature the file is memory-mapped; otherwise it
+//! is read in batches of chunks, so memory use is bounded by about
+//! two chunks per `rayon` thread, whatever the file size.
+
+use crate::{Error, Result, count_newlines, get_progressbar};
+use indicatif::ProgressBar;
+use rayon::prelude::*;
+use std::{
+    borrow::Cow,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "fast-lines")]
+use memmap2::Mmap;
+
+/// Default approximate size of the chunks processed by each task.
+pub const PAR_LINES_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/**
+Builder for parallel line processing.
+
+The file is split on newline boundaries into chunks of about
+[`PAR_LINES_CHUNK_SIZE`] bytes, and the chunks are processed in parallel.
+
+Each line is passed as `&[u8]` (without `\n` or `\r\n`), with its line number
+(starting at 1). A final line without a newline is included.
+
+Example:
+```
+    use claudiofsr_lib::ParLines;
+
+    let path = "/tmp/par_lines_example.txt";
+    std::fs::write(path, "|0000|017|\r\n|C100|0|\r\n|C170|1|\r\n|9999|4|").unwrap();
+
+    // Results in file order.
+    let registers: Vec<(u64, String)> = ParLines::new(path)
+        .map(|line_number, line| {
+            let register = line.split(|&b| b == b'|').nth(1).unwrap_or_default();
+            (line_number, String::from_utf8_lossy(register).into_owned())
+        })
+        .unwrap();
+
+    assert_eq!(registers[1], (2, "C100".to_string()));
+    assert_eq!(registers.len(), 4);
+
+    // Or fold the lines with a reducer.
+    let total_bytes: usize = ParLines::new(path)
+        .chunk_size(8)
+        .fold(|| 0, |sum, _, line| sum + line.len(), |a, b| a + b)
+        .unwrap();
+
+    assert_eq!(total_bytes, 10 + 8 + 8 + 8);
+
+    std::fs::remove_file(path).unwrap();
+```
+*/
+#[derive(Debug, Clone)]
+pub struct ParLines {
+    path: PathBuf,
+    chunk_size: usize,
+    progress: Option<Cow<'static, str>>,
+}
+
+impl ParLines {
+    /// Creates the builder for the file at `path`.
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        ParLines {
+            path: path.as_ref().to_path_buf(),
+            chunk_size: PAR_LINES_CHUNK_SIZE,
+            progress: None,
+        }
+    }
+
+    /// Sets the approximate chunk size in bytes (at least 1).
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Displays a progress bar by bytes processed, with the given message
+    /// (see [`get_progressbar`]).
+    pub fn progress(mut self, msg: impl Into<Cow<'static, str>>) -> Self {
+        self.progress = Some(msg.into());
+        self
+    }
+
+    /**
+    Applies `f` to every line in parallel and returns the results in file order.
+
+    ### Errors
+    Returns an I/O error (with the path) if the file cannot be read.
+    */
+    pub fn map<T, F>(&self, f: F) -> Result<Vec<T>>
+    where
+        T: Send,
+        F: Fn(u64, &[u8]) -> T + Sync,
+    {
+        let chunks: Vec<Vec<T>> = self.run(|first_line, chunk| {
+            lines(chunk)
+                .zip(first_line..)
+                .map(|(line, line_number)| f(line_number, line))
+                .collect()
+        })?;
+
+        Ok(chunks.into_iter().flatten().collect())
+    }
+
+    /**
+    Folds the lines of each chunk in parallel, starting from `identity()`,
+    then combines the chunk results in file order with `reduce`.
+
+    `reduce` must be associative; it does not need to be commutative.
+    */
+    pub fn fold<A, ID, F, R>(&self, identity: ID, fold: F, reduce: R) -> Result<A>
+    where
+        A: Send,
+        ID: Fn() -> A + Sync,
+        F: Fn(A, u64, &[u8]) -> A + Sync,
+        R: Fn(A, A) -> A,
+    {
+        let chunks: Vec<A> = self.run(|first_line, chunk| {
+            lines(chunk)
+                .zip(first_line..)
+                .fold(identity(), |acc, (line, line_number)| {
+                    fold(acc, line_number, line)
+                })
+        })?;
+
+        Ok(chunks.into_iter().reduce(reduce).unwrap_or_else(identity))
+    }
+
+    /// Splits the file and applies `process(first_line_number, chunk)` to each chunk.
+    fn run<T, F>(&self, process: F) -> Result<Vec<T>>
+    where
+        T: Send,
+        F: Fn(u64, &[u8]) -> T + Sync,
+    {
+        let path = self.path.as_path();
+        let file = File::open(path).map_err(|error| Error::io(path, error))?;
+        let size = file
+            .metadata()
+            .map_err(|error| Error::io(path, error))?
+            .len();
+
+        let progressbar: Option<ProgressBar> = match &self.progress {
+            Some(msg) => Some(get_progressbar(msg.clone(), size.try_into()?)?),
+            None => None,
+        };
+
+        let mut batch = Batch {
+            results: Vec::new(),
+            next_line: 1,
+            progressbar: progressbar.as_ref(),
+        };
+        for_each_batch(&file, path, self.chunk_size, |chunks| {
+            batch.process(chunks, &process)
+        })?;
+
+        if let Some(pb) = &progressbar {
+            pb.finish();
+        }
+
+        Ok(batch.results)
+    }
+}
+
+/// Results of the chunks processed so far.
+struct Batch<'a, T> {
+    results: Vec<T>,
+    /// Line number of the first line of the next chunk.
+    next_line: u64,
+    progressbar: Option<&'a ProgressBar>,
+}
+
+impl<T: Send> Batch<'_, T> {
+    /// Processes `chunks` (consecutive in the file) in parallel.
+    fn process<F>(&mut self, chunks: &[&[u8]], process: &F)
+    where
+        F: Fn(u64, &[u8]) -> T + Sync,
+    {
+        // Line number of the first line of each chunk.
+        let counts: Vec<u64> = chunks.par_iter().map(|c| count_newlines(c)).collect();
+        let first_lines: Vec<u64> = counts
+            .iter()
+            .scan(self.next_line, |next, &count| {
+                let first = *next;
+                *next += count;
+                Some(first)
+            })
+            .collect();
+        self.next_line += counts.iter().sum::<u64>();
+
+        let progressbar = self.progressbar;
+        let results: Vec<T> = chunks
+            .par_iter()
+            .zip(first_lines)
+            .map(|(chunk, first_line)| {
+                let result = process(first_line, chunk);
+                if let Some(pb) = progressbar {
+                    pb.inc(chunk.len() as u64);
+                }
+                result
+            })
+            .collect();
+
+        self.results.extend(results);
+    }
+}
+
+/**
+Applies `f` to every line of the file in parallel, returning the results in file order.
+
+Shortcut for [`ParLines::new(path).map(f)`](ParLines::map).
+
+Example:
+```
+    use claudiofsr_lib::par_lines;
+
+    let path = "/tmp/par_lines_fn_example.txt";
+    std::fs::write(path, "a\nbb\n\nccc").unwrap();
+
+    let lengths: Vec<(u64, usize)> = par_lines(path, |n, line| (n, line.len())).unwrap();
+    assert_eq!(lengths, [(1, 1), (2, 2), (3, 0), (4, 3)]);
+
+    std::fs::remove_file(path).unwrap();
+```
+*/
+pub fn par_lines<P, T, F>(path: P, f: F) -> Result<Vec<T>>
+where
+    P: AsRef<Path>,
+    T: Send,
+    F: Fn(u64, &[u8]) -> T + Sync,
+{
+    ParLines::new(path).map(f)
+}
+
+/// Maps the file and passes all its chunks to `process` at once.
+#[cfg(feature = "fast-lines")]
+fn for_each_batch<F>(file: &File, path: &Path, chunk_size: usize, mut process: F) -> Result<()>
+where
+    F: FnMut(&[&[u8]]),
+{
+    // Safety: the file must not be modified while mapped (see FileExtension::count_lines).
+    let mmap = unsafe { Mmap::map(file) }.map_err(|error| Error::io(path, error))?;
+    process(&split_chunks(&mmap, chunk_size));
+    Ok(())
+}
+
+/// Reads the file in batches of chunks and passes each batch to `process`.
+///
+/// A batch has two chunks per `rayon` thread, so only one batch is in memory.
+#[cfg(not(feature = "fast-lines"))]
+fn for_each_batch<F>(file: &File, path: &Path, chunk_size: usize, mut process: F) -> Result<()>
+where
+    F: FnMut(&[&[u8]]),
+{
+    use std::io::{BufRead, BufReader, Read};
+
+    let batch_len = rayon::current_num_threads() * 2;
+    let mut reader = BufReader::new(file);
+    let mut buffers: Vec<Vec<u8>> = vec![Vec::new(); batch_len];
+
+    loop {
+        let mut filled = 0;
+        for buffer in &mut buffers {
+            buffer.clear();
+            // Split after the first newline following `chunk_size` bytes, as `split_chunks`.
+            let read = (&mut reader)
+                .take(chunk_size as u64)
+                .read_to_end(buffer)
+                .map_err(|error| Error::io(path, error))?;
+            if read == chunk_size {
+                reader
+                    .read_until(b'\n', buffer)
+                    .map_err(|error| Error::io(path, error))?;
+            }
+            if buffer.is_empty() {
+                break;
+            }
+            filled += 1;
+        }
+
+        if filled == 0 {
+            return Ok(());
+        }
+
+        let chunks: Vec<&[u8]> = buffers[..filled].iter().map(Vec::as_slice).collect();
+        process(&chunks);
+    }
+}
+
+/// Splits `data` after the first newline following every `chunk_size` bytes.
+#[cfg(feature = "fast-lines")]
+fn split_chunks(data: &[u8], chunk_size: usize) -> Vec<&[u8]> {
+    let mut chunks = Vec::new();
+    let mut rest = data;
+
+    while rest.len() > chunk_size {
+        let end = match rest[chunk_size..].iter().position(|&b| b == b'\n') {
+            Some(index) => chunk_size + index + 1,
+            None => rest.len(),
+        };
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+
+    if !rest.is_empty() {
+        chunks.push(rest);
+    }
+
+    chunks
+}
+
+/// Iterates the lines of a non-empty chunk, without `\n` or `\r\n`.
+fn lines(chunk: &[u8]) -> impl Iterator<Item = &[u8]> {
+    chunk
+        .strip_suffix(b"\n")
+        .unwrap_or(chunk)
+        .split(|&b| b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output par_lines_tests
+// cargo test --features fast-lines -- --show-output par_lines_tests
+
+#[cfg(test)]
+mod par_lines_tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn same_as_sequential_for_every_chunk_size() -> Result<()> {
+        let path = "/tmp/par_lines_tests_chunks.txt";
+        let content = "first\r\n\nthird line\n\r\nfifth\nsixth";
+        fs::write(path, content)?;
+
+        let expected: Vec<(u64, String)> = content
+            .lines()
+            .zip(1..)
+            .map(|(line, n)| (n, line.to_string()))
+            .collect();
+
+        for chunk_size in 1..=content.len() + 1 {
+            let result = ParLines::new(path)
+                .chunk_size(chunk_size)
+                .map(|n, line| (n, String::from_utf8_lossy(line).into_owned()))?;
+            assert_eq!(result, expected, "chunk_size: {chunk_size}");
+        }
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn empty_file_and_trailing_newline() -> Result<()> {
+        let path = "/tmp/par_lines_tests_empty.txt";
+
+        fs::write(path, "")?;
+        assert!(par_lines(path, |n, _| n)?.is_empty());
+        assert_eq!(
+            ParLines::new(path).fold(|| 7, |a, _, _| a + 1, |a, b| a + b)?,
+            7
+        );
+
+        fs::write(path, "\n")?;
+        assert_eq!(par_lines(path, |n, line| (n, line.len()))?, [(1, 0)]);
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn fold_keeps_file_order() -> Result<()> {
+        let path = "/tmp/par_lines_tests_fold.txt";
+        let content: String = (1..=10_000).map(|i| format!("{i}\n")).collect();
+        fs::write(path, &content)?;
+
+        // String concatenation is associative but not commutative.
+        let joined = ParLines::new(path).chunk_size(100).fold(
+            String::new,
+            |mut acc, n, line| {
+                assert_eq!(line, n.to_string().as_bytes());
+                acc.push_str(std::str::from_utf8(line).unwrap());
+                acc.push('\n');
+                acc
+            },
+            |a, b| a + &b,
+        )?;
+
+        assert_eq!(joined, content);
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn missing_file_error_has_path() {
+        let error = par_lines("/tmp/par_lines_tests_missing.txt", |n, _| n).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("/tmp/par_lines_tests_missing.txt: ")
+        );
+    }
+}
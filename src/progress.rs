@@ -0,0 +1,405 @@
+//! # Progress Bars
+//!
+//! A builder for `indicatif` progress bars with named templates,
+//! and a manager for nested bars (e.g. files → lines).
+//!
+//! Bars are hidden when stderr is not a terminal, so redirected
+//! output and tests are not filled with progress lines.
+
+use crate::Result;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::{
+    borrow::Cow,
+    io::{self, IsTerminal},
+};
+
+/// Default width of the `{msg}` field.
+pub const PROGRESS_MSG_WIDTH: usize = 38;
+
+/// Progress bar templates.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Template {
+    /// `msg spinner [bar] percent/100% (eta) [elapsed]`
+    #[default]
+    Full,
+    /// `msg spinner [bar] percent/100% (eta)`
+    Percent,
+    /// `msg spinner [bar] pos/len (eta)`
+    Count,
+    /// `[elapsed] bar pos/len msg`
+    Elapsed,
+    /// A custom `indicatif` template.
+    ///
+    /// <https://docs.rs/indicatif/latest/indicatif/#templates>
+    Custom(String),
+}
+
+impl Template {
+    /// Returns the named template at `index` (0 to 3), as used by [`get_style`].
+    pub fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Template::Full),
+            1 => Some(Template::Percent),
+            2 => Some(Template::Count),
+            3 => Some(Template::Elapsed),
+            _ => None,
+        }
+    }
+
+    /// Returns the template string, with `{msg}` padded to `msg_width`.
+    pub fn to_template_string(&self, msg_width: usize) -> Cow<'_, str> {
+        let w = msg_width;
+        match self {
+            Template::Full => Cow::Owned(format!(
+                "{{msg:{w}}} {{spinner:.green}} [{{wide_bar:.cyan/blue}}] {{percent}}/100% ({{eta}}) [{{elapsed_precise}}]"
+            )),
+            Template::Percent => Cow::Owned(format!(
+                "{{msg:{w}}} {{spinner:.green}} [{{wide_bar:.cyan/blue}}] {{percent}}/100% ({{eta}})"
+            )),
+            Template::Count => Cow::Owned(format!(
+                "{{msg:{w}}} {{spinner:.green}} [{{wide_bar:.cyan/blue}}] {{pos}}/{{len}} ({{eta}})"
+            )),
+            Template::Elapsed => Cow::Owned(format!(
+                "[{{elapsed_precise}}] {{bar:40.cyan/blue}} {{pos}}/{{len}} {{msg:{w}}}"
+            )),
+            Template::Custom(template) => Cow::Borrowed(template),
+        }
+    }
+}
+
+/// Characters used to draw the filled, current and empty parts of the bar.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ProgressChars {
+    /// `#>-`
+    #[default]
+    Arrow,
+    /// `## `
+    Hash,
+    /// `■□ `
+    Squares,
+    /// `█░-`
+    Blocks,
+    /// `🦀👾👻`
+    Crab,
+    /// Custom characters (at least 2, see `ProgressStyle::progress_chars`).
+    Custom(String),
+}
+
+impl ProgressChars {
+    /// Returns the named characters at `index` (0 to 4), as used by [`get_style`].
+    pub fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(ProgressChars::Arrow),
+            1 => Some(ProgressChars::Hash),
+            2 => Some(ProgressChars::Squares),
+            3 => Some(ProgressChars::Blocks),
+            4 => Some(ProgressChars::Crab),
+            _ => None,
+        }
+    }
+
+    /// Returns the characters as a string.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ProgressChars::Arrow => "#>-",
+            ProgressChars::Hash => "## ",
+            ProgressChars::Squares => "■□ ",
+            ProgressChars::Blocks => "█░-",
+            ProgressChars::Crab => "🦀👾👻",
+            ProgressChars::Custom(chars) => chars,
+        }
+    }
+}
+
+/**
+Builder for a progress bar.
+
+The bar is drawn to stderr, or hidden if stderr is not a terminal
+(or if [`hidden`](Progress::hidden) is set).
+
+Example:
+```
+    use claudiofsr_lib::{Progress, ProgressChars, Template};
+
+    let files = vec!["a.txt", "b.txt"];
+
+    let pb = Progress::new(files.len())
+        .message(format!("Reading {} files", files.len()))
+        .template(Template::Count)
+        .chars(ProgressChars::Blocks)
+        .build()
+        .unwrap();
+
+    for _file in &files {
+        pb.inc(1);
+    }
+    pb.finish();
+
+    assert_eq!(pb.position(), 2);
+    assert_eq!(pb.message(), "Reading 2 files");
+
+    // Template errors are returned, not panics.
+    let error = Progress::new(1).template(Template::Custom("{msg:x}".into())).build();
+    assert!(error.is_err());
+```
+*/
+#[derive(Debug, Clone)]
+pub struct Progress {
+    total: u64,
+    message: Cow<'static, str>,
+    template: Template,
+    chars: ProgressChars,
+    msg_width: usize,
+    hidden: bool,
+}
+
+impl Progress {
+    /// Creates the builder for a bar with `total` steps.
+    pub fn new(total: usize) -> Self {
+        Progress {
+            total: total as u64,
+            message: Cow::Borrowed(""),
+            template: Template::default(),
+            chars: ProgressChars::default(),
+            msg_width: PROGRESS_MSG_WIDTH,
+            hidden: false,
+        }
+    }
+
+    /// Sets the message (a `&'static str` or an owned `String`).
+    pub fn message(mut self, message: impl Into<Cow<'static, str>>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Sets the template.
+    pub fn template(mut self, template: Template) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Sets the progress characters.
+    pub fn chars(mut self, chars: ProgressChars) -> Self {
+        self.chars = chars;
+        self
+    }
+
+    /// Sets the width of the `{msg}` field in the named templates.
+    pub fn msg_width(mut self, msg_width: usize) -> Self {
+        self.msg_width = msg_width;
+        self
+    }
+
+    /// Hides the bar (no-op mode): it still counts, but draws nothing.
+    pub fn hidden(mut self, hidden: bool) -> Self {
+        self.hidden = hidden;
+        self
+    }
+
+    /// Returns the `ProgressStyle`, or an error if the template is invalid.
+    pub fn style(&self) -> Result<ProgressStyle> {
+        let template = self.template.to_template_string(self.msg_width);
+        let style = ProgressStyle::default_bar()
+            .template(&template)?
+            .progress_chars(self.chars.as_str());
+        Ok(style)
+    }
+
+    /// Builds the progress bar.
+    pub fn build(self) -> Result<ProgressBar> {
+        let style = self.style()?;
+        let target = if self.hidden {
+            ProgressDrawTarget::hidden()
+        } else {
+            stderr_target()
+        };
+
+        let pb = ProgressBar::with_draw_target(Some(self.total), target)
+            .with_style(style)
+            .with_message(self.message);
+
+        Ok(pb)
+    }
+}
+
+/// Draws to stderr if it is a terminal; hides otherwise.
+fn stderr_target() -> ProgressDrawTarget {
+    if cfg!(test) || !io::stderr().is_terminal() {
+        ProgressDrawTarget::hidden()
+    } else {
+        ProgressDrawTarget::stderr()
+    }
+}
+
+/**
+Manages several progress bars drawn together (e.g. files → lines).
+
+Wraps `indicatif::MultiProgress`. All bars are hidden when stderr is not
+a terminal, or when created with [`ProgressManager::hidden`].
+
+Example:
+```
+    use claudiofsr_lib::{Progress, ProgressManager, Template};
+
+    let files = [("a.txt", 3), ("b.txt", 2)];
+
+    let manager = ProgressManager::new();
+    let total = manager.add(Progress::new(files.len()).message("Files")).unwrap();
+
+    for (name, lines) in files {
+        let progress = Progress::new(lines)
+            .message(format!("Lines of {name}"))
+            .template(Template::Count);
+        let pb = manager.add_child(&total, progress).unwrap();
+
+        for _ in 0..lines {
+            pb.inc(1);
+        }
+        manager.finish_and_remove(&pb);
+        total.inc(1);
+    }
+    total.finish();
+
+    assert_eq!(total.position(), 2);
+```
+*/
+#[derive(Debug, Clone)]
+pub struct ProgressManager {
+    multi: MultiProgress,
+}
+
+impl Default for ProgressManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressManager {
+    /// Creates a manager that draws to stderr (hidden if stderr is not a terminal).
+    pub fn new() -> Self {
+        ProgressManager {
+            multi: MultiProgress::with_draw_target(stderr_target()),
+        }
+    }
+
+    /// Creates a manager whose bars draw nothing.
+    pub fn hidden() -> Self {
+        ProgressManager {
+            multi: MultiProgress::with_draw_target(ProgressDrawTarget::hidden()),
+        }
+    }
+
+    /// Returns true if the bars are not drawn.
+    pub fn is_hidden(&self) -> bool {
+        self.multi.is_hidden()
+    }
+
+    /// Builds a bar and adds it below the existing bars.
+    pub fn add(&self, progress: Progress) -> Result<ProgressBar> {
+        Ok(self.multi.add(progress.build()?))
+    }
+
+    /// Builds a bar and adds it right below `parent` (for nested tasks).
+    pub fn add_child(&self, parent: &ProgressBar, progress: Progress) -> Result<ProgressBar> {
+        Ok(self.multi.insert_after(parent, progress.build()?))
+    }
+
+    /// Finishes `pb` and removes it from the display.
+    pub fn finish_and_remove(&self, pb: &ProgressBar) {
+        pb.finish_and_clear();
+        self.multi.remove(pb);
+    }
+
+    /// Prints a line above the bars (nothing if hidden).
+    pub fn println(&self, msg: impl AsRef<str>) -> io::Result<()> {
+        self.multi.println(msg)
+    }
+
+    /// Hides the bars while `f` runs (e.g. to write to stdout).
+    pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        self.multi.suspend(f)
+    }
+
+    /// Clears all bars from the display.
+    pub fn clear(&self) -> io::Result<()> {
+        self.multi.clear()
+    }
+
+    /// Returns the inner `MultiProgress`.
+    pub fn multi_progress(&self) -> &MultiProgress {
+        &self.multi
+    }
+}
+
+/// Command line progress with indicatif ProgressBar
+///
+/// Uses [`Template::Full`] and [`ProgressChars::Arrow`];
+/// see [`Progress`] for other styles.
+pub fn get_progressbar(msg: impl Into<Cow<'static, str>>, total: usize) -> Result<ProgressBar> {
+    Progress::new(total).message(msg).build()
+}
+
+/// Genarate ProgressStyle by template and progress characters.
+///
+/// Returns an error if an index is out of range
+/// (see [`Template::from_index`] and [`ProgressChars::from_index`]).
+pub fn get_style(
+    template_index: usize,
+    chars_index: usize,
+    length: usize,
+) -> Result<ProgressStyle> {
+    let template = Template::from_index(template_index)
+        .ok_or_else(|| format!("invalid template index: {template_index} (expected 0 to 3)"))?;
+    let chars = ProgressChars::from_index(chars_index)
+        .ok_or_else(|| format!("invalid progress chars index: {chars_index} (expected 0 to 4)"))?;
+
+    Progress::new(0)
+        .template(template)
+        .chars(chars)
+        .msg_width(length)
+        .style()
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output progress_tests
+
+#[cfg(test)]
+mod progress_tests {
+    use super::*;
+
+    #[test]
+    fn get_style_out_of_range_is_an_error() {
+        for template_index in 0..4 {
+            for chars_index in 0..5 {
+                assert!(get_style(template_index, chars_index, 38).is_ok());
+            }
+        }
+
+        let error = get_style(4, 0, 38).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "invalid template index: 4 (expected 0 to 3)"
+        );
+        assert!(get_style(0, 5, 38).is_err());
+    }
+
+    #[test]
+    fn owned_messages_and_hidden_bars() -> Result<()> {
+        let name = String::from("file.txt");
+        let pb = get_progressbar(format!("Reading {name}"), 10)?;
+        pb.inc(4);
+
+        assert!(pb.is_hidden());
+        assert_eq!(pb.position(), 4);
+        assert_eq!(pb.length(), Some(10));
+        assert_eq!(pb.message(), "Reading file.txt");
+
+        let manager = ProgressManager::new();
+        assert!(manager.is_hidden());
+        assert!(ProgressManager::hidden().is_hidden());
+        Ok(())
+    }
+}
@@ -0,0 +1,368 @@
+//! # Progress Adapters
+//!
+//! Wraps an `Iterator`, a rayon `ParallelIterator` or a `Read` so that it
+//! advances a progress bar automatically: by items for iterators and by
+//! bytes for readers.
+//!
+//! When the source is exhausted, the bar is finished with a summary
+//! message, e.g. `Reading files: 1,234 items in 2 seconds`.
+
+use crate::{Progress, Result};
+use indicatif::{HumanBytes, HumanCount, HumanDuration, ProgressBar};
+use rayon::iter::{ParallelIterator, plumbing::UnindexedConsumer};
+use std::{
+    borrow::Cow,
+    io::{self, Read},
+};
+
+/// What the progress bar counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Items,
+    Bytes,
+}
+
+/// A progress bar that is finished (once) with a summary message.
+#[derive(Debug, Clone)]
+struct Tracker {
+    pb: ProgressBar,
+    unit: Unit,
+    message: Cow<'static, str>,
+}
+
+impl Tracker {
+    fn new(pb: ProgressBar, unit: Unit) -> Self {
+        let message = pb.message().into();
+        Tracker { pb, unit, message }
+    }
+
+    fn finish(&self) {
+        if self.pb.is_finished() {
+            return;
+        }
+
+        let position = self.pb.position();
+        let amount = match self.unit {
+            Unit::Items => format!("{} items", HumanCount(position)),
+            Unit::Bytes => HumanBytes(position).to_string(),
+        };
+        let elapsed = HumanDuration(self.pb.elapsed());
+
+        // The length may have been an estimate (e.g. from `size_hint`).
+        self.pb.set_length(position);
+
+        let summary = if self.message.is_empty() {
+            format!("{amount} in {elapsed}")
+        } else {
+            format!("{}: {amount} in {elapsed}", self.message)
+        };
+        self.pb.finish_with_message(summary);
+    }
+}
+
+/// Iterator that advances a progress bar by one for each item.
+///
+/// See [`IteratorProgressExt`].
+#[derive(Debug, Clone)]
+pub struct ProgressIter<I> {
+    iter: I,
+    tracker: Tracker,
+}
+
+impl<I> ProgressIter<I> {
+    /// Returns the progress bar.
+    pub fn progress_bar(&self) -> &ProgressBar {
+        &self.tracker.pb
+    }
+}
+
+impl<I: Iterator> Iterator for ProgressIter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next();
+        match item {
+            Some(_) => self.tracker.pb.inc(1),
+            None => self.tracker.finish(),
+        }
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<I: ExactSizeIterator> ExactSizeIterator for ProgressIter<I> {}
+
+/**
+Progress bars for iterators.
+
+Example:
+```
+    use claudiofsr_lib::{IteratorProgressExt, Progress, Template};
+
+    let lines = vec!["|0000|", "|C100|", "|9999|"];
+
+    // The length comes from `size_hint`.
+    let mut iter = lines.iter().with_progress("Lines").unwrap();
+    let count = iter.by_ref().filter(|line| line.starts_with("|C")).count();
+    assert_eq!(count, 1);
+
+    let pb = iter.progress_bar();
+    assert_eq!(pb.position(), 3);
+    assert!(pb.is_finished());
+    assert!(pb.message().starts_with("Lines: 3 items in "));
+
+    // Or with a style preset.
+    let progress = Progress::new(100).message("Numbers").template(Template::Count);
+    let sum: u32 = (1..=100).with_progress_style(progress).unwrap().sum();
+    assert_eq!(sum, 5050);
+```
+*/
+pub trait IteratorProgressExt: Iterator + Sized {
+    /// Tracks the iterator with a default bar, whose length is the `size_hint`.
+    fn with_progress(self, msg: impl Into<Cow<'static, str>>) -> Result<ProgressIter<Self>> {
+        let (lower, upper) = self.size_hint();
+        let progress = Progress::new(upper.unwrap_or(lower)).message(msg);
+        self.with_progress_style(progress)
+    }
+
+    /// Tracks the iterator with a bar built from `progress`.
+    fn with_progress_style(self, progress: Progress) -> Result<ProgressIter<Self>> {
+        Ok(self.with_progress_bar(progress.build()?))
+    }
+
+    /// Tracks the iterator with an existing bar (e.g. from a [`ProgressManager`](crate::ProgressManager)).
+    fn with_progress_bar(self, pb: ProgressBar) -> ProgressIter<Self> {
+        ProgressIter {
+            iter: self,
+            tracker: Tracker::new(pb, Unit::Items),
+        }
+    }
+}
+
+impl<I: Iterator> IteratorProgressExt for I {}
+
+/// Parallel iterator that advances a progress bar by one for each item.
+///
+/// See [`ParallelIteratorProgressExt`].
+#[derive(Debug, Clone)]
+pub struct ParProgressIter<I> {
+    iter: I,
+    tracker: Tracker,
+}
+
+impl<I: ParallelIterator> ParallelIterator for ParProgressIter<I> {
+    type Item = I::Item;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        let pb = self.tracker.pb.clone();
+        let result = self
+            .iter
+            .map(move |item| {
+                pb.inc(1);
+                item
+            })
+            .drive_unindexed(consumer);
+
+        self.tracker.finish();
+        result
+    }
+
+    fn opt_len(&self) -> Option<usize> {
+        self.iter.opt_len()
+    }
+}
+
+/**
+Progress bars for rayon parallel iterators.
+
+The bar is finished when the parallel iterator has been consumed.
+
+Example:
+```
+    use claudiofsr_lib::ParallelIteratorProgressExt;
+    use rayon::prelude::*;
+
+    let numbers: Vec<u64> = (1..=1000).collect();
+
+    let squares: Vec<u64> = numbers
+        .par_iter()
+        .with_progress("Squares")
+        .unwrap()
+        .map(|n| n * n)
+        .collect();
+
+    assert_eq!(squares[999], 1_000_000);
+```
+*/
+pub trait ParallelIteratorProgressExt: ParallelIterator {
+    /// Tracks the iterator with a default bar, whose length is `opt_len` (or 0).
+    fn with_progress(self, msg: impl Into<Cow<'static, str>>) -> Result<ParProgressIter<Self>> {
+        let progress = Progress::new(self.opt_len().unwrap_or(0)).message(msg);
+        self.with_progress_style(progress)
+    }
+
+    /// Tracks the iterator with a bar built from `progress`.
+    fn with_progress_style(self, progress: Progress) -> Result<ParProgressIter<Self>> {
+        Ok(self.with_progress_bar(progress.build()?))
+    }
+
+    /// Tracks the iterator with an existing bar.
+    fn with_progress_bar(self, pb: ProgressBar) -> ParProgressIter<Self> {
+        ParProgressIter {
+            iter: self,
+            tracker: Tracker::new(pb, Unit::Items),
+        }
+    }
+}
+
+impl<I: ParallelIterator> ParallelIteratorProgressExt for I {}
+
+/// Reader that advances a progress bar by the number of bytes read.
+///
+/// See [`ReadProgressExt`].
+#[derive(Debug)]
+pub struct ProgressReader<R> {
+    reader: R,
+    tracker: Tracker,
+}
+
+impl<R> ProgressReader<R> {
+    /// Returns the progress bar.
+    pub fn progress_bar(&self) -> &ProgressBar {
+        &self.tracker.pb
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        if n > 0 {
+            self.tracker.pb.inc(n as u64);
+        } else if !buf.is_empty() {
+            self.tracker.finish();
+        }
+        Ok(n)
+    }
+}
+
+/**
+Progress bars for readers, by bytes read.
+
+Example:
+```
+    use claudiofsr_lib::ReadProgressExt;
+    use std::io::Read;
+
+    let data = vec![b'x'; 10_000];
+
+    let mut reader = data.as_slice().with_progress("Reading", data.len()).unwrap();
+    let mut content = Vec::new();
+    reader.read_to_end(&mut content).unwrap();
+
+    let pb = reader.progress_bar();
+    assert_eq!(pb.position(), 10_000);
+    assert!(pb.message().starts_with("Reading: 9.77 KiB in "));
+```
+*/
+pub trait ReadProgressExt: Read + Sized {
+    /// Tracks the reader with a default bar of `total` bytes
+    /// (e.g. the file length from `metadata`).
+    fn with_progress(
+        self,
+        msg: impl Into<Cow<'static, str>>,
+        total: usize,
+    ) -> Result<ProgressReader<Self>> {
+        self.with_progress_style(Progress::new(total).message(msg))
+    }
+
+    /// Tracks the reader with a bar built from `progress`.
+    fn with_progress_style(self, progress: Progress) -> Result<ProgressReader<Self>> {
+        Ok(self.with_progress_bar(progress.build()?))
+    }
+
+    /// Tracks the reader with an existing bar.
+    fn with_progress_bar(self, pb: ProgressBar) -> ProgressReader<Self> {
+        ProgressReader {
+            reader: self,
+            tracker: Tracker::new(pb, Unit::Bytes),
+        }
+    }
+}
+
+impl<R: Read> ReadProgressExt for R {}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output progress_iter_tests
+
+#[cfg(test)]
+mod progress_iter_tests {
+    use super::*;
+    use crate::ProgressManager;
+    use rayon::prelude::*;
+
+    #[test]
+    fn finishes_only_when_exhausted() -> Result<()> {
+        let mut iter = [1, 2, 3].into_iter().with_progress("Numbers")?;
+        assert_eq!(iter.len(), 3);
+
+        iter.next();
+        assert!(!iter.progress_bar().is_finished());
+
+        assert_eq!(iter.by_ref().count(), 2);
+        let pb = iter.progress_bar().clone();
+        assert!(pb.is_finished());
+        assert_eq!(pb.position(), 3);
+
+        // Polling again does not replace the summary.
+        let summary = pb.message();
+        assert!(iter.next().is_none());
+        assert_eq!(pb.message(), summary);
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_counts_every_item() -> Result<()> {
+        let manager = ProgressManager::hidden();
+        let pb = manager.add(Progress::new(10_000))?;
+
+        let sum: u64 = (0..10_000u64)
+            .into_par_iter()
+            .filter(|n| n % 2 == 0)
+            .with_progress_bar(pb.clone())
+            .sum();
+
+        assert_eq!(sum, 24_995_000);
+        assert_eq!(pb.position(), 5_000);
+        assert!(pb.is_finished());
+        assert!(pb.message().starts_with("5,000 items in "));
+        Ok(())
+    }
+
+    #[test]
+    fn reader_counts_bytes() -> Result<()> {
+        let mut reader = io::repeat(b'a').take(3000).with_progress("Bytes", 3000)?;
+
+        let mut buffer = [0; 1024];
+        assert_eq!(reader.read(&mut buffer)?, 1024);
+        assert!(!reader.progress_bar().is_finished());
+
+        io::copy(&mut reader, &mut io::sink())?;
+        assert_eq!(reader.progress_bar().position(), 3000);
+        assert!(reader.progress_bar().is_finished());
+        Ok(())
+    }
+}
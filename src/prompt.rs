@@ -0,0 +1,274 @@
+//! # Interactive Prompts
+//!
+//! Confirmation, selection and validated input for command line tools.
+//!
+//! [`Prompter`] reads answers from any `BufRead` and writes questions to
+//! any `Write`, so prompts can be tested without a terminal.
+//! Invalid answers print an error and ask again.
+
+use crate::{Colors, Stream, colors_enabled};
+use std::{
+    fmt::Display,
+    io::{self, BufRead, Write},
+};
+
+/**
+Asks questions on `W` and reads the answers from `R`.
+
+Example:
+```
+    use claudiofsr_lib::Prompter;
+
+    let answers = "talvez\ns\n2\n123\n12.345.678/0001-95\n";
+    let mut output = Vec::new();
+    let mut prompter = Prompter::new(answers.as_bytes(), &mut output);
+
+    // Yes or no (also accepts "s"/"sim" and "não").
+    assert!(prompter.confirm("Overwrite output?", Some(false)).unwrap());
+
+    // One of several options: returns the index.
+    let periods = ["2024-01", "2024-02", "2024-03"];
+    assert_eq!(prompter.select("Period:", &periods).unwrap(), 1);
+
+    // Input validated (and converted) by a closure.
+    let cnpj: String = prompter
+        .input_with("CNPJ:", |text| {
+            let digits: String = text.chars().filter(char::is_ascii_digit).collect();
+            if digits.len() == 14 { Ok(digits) } else { Err("CNPJ must have 14 digits") }
+        })
+        .unwrap();
+    assert_eq!(cnpj, "12345678000195");
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Please answer yes or no."));
+    assert!(output.contains("  2) 2024-02\n"));
+    assert!(output.contains("CNPJ must have 14 digits"));
+```
+*/
+#[derive(Debug)]
+pub struct Prompter<R, W> {
+    reader: R,
+    writer: W,
+    colors: bool,
+}
+
+impl Prompter<io::StdinLock<'static>, io::Stderr> {
+    /// Reads from stdin and writes to stderr, keeping stdout for the program output.
+    ///
+    /// Colors are enabled as for stderr (see [`colors_enabled`]).
+    pub fn stdio() -> Self {
+        Prompter {
+            reader: io::stdin().lock(),
+            writer: io::stderr(),
+            colors: colors_enabled(Stream::Stderr),
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    /// Reads answers from `reader` and writes questions to `writer`, without colors.
+    pub fn new(reader: R, writer: W) -> Self {
+        Prompter {
+            reader,
+            writer,
+            colors: false,
+        }
+    }
+
+    /// Writes the questions and errors with colors or not.
+    pub fn colors(mut self, enabled: bool) -> Self {
+        self.colors = enabled;
+        self
+    }
+
+    /// Returns the reader and the writer.
+    pub fn into_inner(self) -> (R, W) {
+        (self.reader, self.writer)
+    }
+
+    /**
+    Asks a yes or no question.
+
+    Accepts `y`, `yes`, `s`, `sim`, `n`, `no`, `não` and `nao` (in any case).
+    An empty answer returns `default`, or asks again if there is no default.
+
+    ### Errors
+    Returns `UnexpectedEof` if the input ends, or an I/O error.
+    */
+    pub fn confirm(&mut self, question: &str, default: Option<bool>) -> io::Result<bool> {
+        let hint = match default {
+            Some(true) => "[Y/n]",
+            Some(false) => "[y/N]",
+            None => "[y/n]",
+        };
+
+        loop {
+            self.ask(question, Some(hint))?;
+            let answer = self.read_answer()?;
+
+            match (answer.to_lowercase().as_str(), default) {
+                ("", Some(default)) => return Ok(default),
+                ("y" | "yes" | "s" | "sim", _) => return Ok(true),
+                ("n" | "no" | "não" | "nao", _) => return Ok(false),
+                _ => self.error("Please answer yes or no.")?,
+            }
+        }
+    }
+
+    /**
+    Asks to choose one of `options`, listed with numbers starting at 1.
+
+    Returns the index (starting at 0) of the chosen option.
+
+    ### Errors
+    Returns `InvalidInput` if `options` is empty, `UnexpectedEof` if the
+    input ends, or an I/O error.
+    */
+    pub fn select<T: Display>(&mut self, question: &str, options: &[T]) -> io::Result<usize> {
+        if options.is_empty() {
+            let msg = "select requires at least one option";
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+        }
+
+        self.ask(question, None)?;
+        writeln!(self.writer)?;
+        for (number, option) in (1..).zip(options) {
+            writeln!(self.writer, "  {number}) {option}")?;
+        }
+
+        let hint = format!("[1-{}]", options.len());
+        loop {
+            self.ask("Choose", Some(&hint))?;
+            let answer = self.read_answer()?;
+
+            match answer.parse::<usize>() {
+                Ok(number) if (1..=options.len()).contains(&number) => return Ok(number - 1),
+                _ => self.error(&format!(
+                    "Please enter a number from 1 to {}.",
+                    options.len()
+                ))?,
+            }
+        }
+    }
+
+    /**
+    Asks for a line of text (without leading or trailing whitespace).
+
+    ### Errors
+    Returns `UnexpectedEof` if the input ends, or an I/O error.
+    */
+    pub fn input(&mut self, question: &str) -> io::Result<String> {
+        self.input_with(question, |text| Ok::<_, &str>(text.to_string()))
+    }
+
+    /**
+    Asks for a line of text until `validate` accepts it.
+
+    `validate` receives the trimmed answer and returns the value,
+    or an error message that is printed before asking again.
+
+    ### Errors
+    Returns `UnexpectedEof` if the input ends, or an I/O error.
+    */
+    pub fn input_with<T, E, F>(&mut self, question: &str, mut validate: F) -> io::Result<T>
+    where
+        E: Display,
+        F: FnMut(&str) -> Result<T, E>,
+    {
+        loop {
+            self.ask(question, None)?;
+            let answer = self.read_answer()?;
+
+            match validate(&answer) {
+                Ok(value) => return Ok(value),
+                Err(error) => self.error(&error.to_string())?,
+            }
+        }
+    }
+
+    /// Writes the question (in bold) and an optional hint.
+    fn ask(&mut self, question: &str, hint: Option<&str>) -> io::Result<()> {
+        write!(self.writer, "{}", question.bold().enabled(self.colors))?;
+        if let Some(hint) = hint {
+            write!(self.writer, " {}", hint.blue().enabled(self.colors))?;
+        }
+        write!(self.writer, " ")?;
+        self.writer.flush()
+    }
+
+    /// Writes an error message (in red).
+    fn error(&mut self, message: &str) -> io::Result<()> {
+        writeln!(self.writer, "{}", message.red().enabled(self.colors))
+    }
+
+    /// Reads a line, trimmed. Fails at the end of the input.
+    fn read_answer(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            writeln!(self.writer)?;
+            let msg = "input ended before a valid answer";
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, msg));
+        }
+        Ok(line.trim().to_string())
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output prompt_tests
+
+#[cfg(test)]
+mod prompt_tests {
+    use super::*;
+
+    fn prompter(input: &str) -> Prompter<&[u8], Vec<u8>> {
+        Prompter::new(input.as_bytes(), Vec::new())
+    }
+
+    #[test]
+    fn confirm_default_and_retry() -> io::Result<()> {
+        assert!(prompter("\n").confirm("Continue?", Some(true))?);
+        assert!(!prompter("\n").confirm("Continue?", Some(false))?);
+        assert!(!prompter("NÃO\n").confirm("Continue?", None)?);
+
+        let mut p = prompter("\nYes\n");
+        assert!(p.confirm("Continue?", None)?);
+        let (_, output) = p.into_inner();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "Continue? [y/n] Please answer yes or no.\nContinue? [y/n] "
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn select_retries_out_of_range() -> io::Result<()> {
+        let mut p = prompter("0\nx\n3\n");
+        assert_eq!(p.select("Period:", &["a", "b", "c"])?, 2);
+
+        let error = prompter("1\n").select::<&str>("Period:", &[]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        Ok(())
+    }
+
+    #[test]
+    fn end_of_input_is_an_error() {
+        let error = prompter("abc\n").confirm("Continue?", None).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let error = prompter("").input("Name:").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn colored_question() -> io::Result<()> {
+        let mut p = prompter("  name  \n").colors(true);
+        assert_eq!(p.input("Name:")?, "name");
+
+        let (_, output) = p.into_inner();
+        assert_eq!(output, b"\x1b[1mName:\x1b[0m ");
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use crate::MyResult;
+use crate::{Error, Result};
 use std::{
     cell::RefCell,
     time::{SystemTime, UNIX_EPOCH},
@@ -67,11 +67,13 @@ pub fn rand() -> u64 {
 /// * `max` - The upper bound of the range (inclusive).
 ///
 /// ### Errors
-/// Returns an error if `min > max`.
-pub fn random_in_range(min: u64, max: u64) -> MyResult<u64> {
+/// Returns [`Error::InvalidRange`] if `min > max`.
+pub fn random_in_range(min: u64, max: u64) -> Result<u64> {
     if min > max {
-        let msg = format!("min ({min}) must be less than or equal to max ({max})");
-        return Err(msg.into());
+        return Err(Error::InvalidRange {
+            min: min.into(),
+            max: max.into(),
+        });
     }
 
     // The number of possible outcomes in the range [min, max].
@@ -231,7 +233,7 @@ mod test_random {
 
     #[test]
     /// Tests that generated values fall within the specified inclusive range.
-    fn random_in_range_bounds() -> MyResult<()> {
+    fn random_in_range_bounds() -> Result<()> {
         let min = 100;
         let max = 200;
         for _ in 0..10_000 {
@@ -248,7 +250,7 @@ mod test_random {
     /// `cargo test -- --show-output random_integers`
     ///
     /// <https://stackoverflow.com/questions/48218459/how-do-i-generate-a-vector-of-random-numbers-in-a-range>
-    fn random_integers() -> MyResult<()> {
+    fn random_integers() -> Result<()> {
         // Example: Get a random integer value in the range 1 to 20:
         let value: u64 = random_in_range(1, 20)?;
 
@@ -277,7 +279,7 @@ mod test_random {
     /// Ensures that an invalid range (min > max) correctly returns an error.
     ///
     /// `cargo test -- --show-output random_in_range_errors_on_invalid_range`
-    fn random_in_range_errors_on_invalid_range() -> MyResult<()> {
+    fn random_in_range_errors_on_invalid_range() -> Result<()> {
         let result = random_in_range(21, 20).map_err(|err| {
             eprintln!("{err}");
             err
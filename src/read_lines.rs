@@ -0,0 +1,355 @@
+//! # Collection Readers
+//!
+//! Reads files written by [`WriteLines`](crate::WriteLines) (or by hand) back
+//! into any collection, parsing each line with `FromStr`.
+
+use crate::{Result, open_file_decoded};
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io::BufRead,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Options for [`read_lines_into_with`] and [`read_pairs_into`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadOptions {
+    /// Separator between key and value when reading pairs (default: `"\t"`).
+    pub separator: String,
+    /// Skips the first line (default: `false`).
+    pub has_header: bool,
+    /// Removes leading and trailing whitespace before parsing (default: `true`).
+    pub trim: bool,
+    /// Skips empty and whitespace-only lines (default: `true`).
+    pub skip_blank: bool,
+    /// Skips lines starting with this prefix, after leading whitespace (default: `"#"`).
+    pub comment_prefix: Option<String>,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        ReadOptions {
+            separator: "\t".to_string(),
+            has_header: false,
+            trim: true,
+            skip_blank: true,
+            comment_prefix: Some("#".to_string()),
+        }
+    }
+}
+
+impl ReadOptions {
+    /// Sets the key/value separator used for pairs.
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Skips the first line.
+    pub fn has_header(mut self, has_header: bool) -> Self {
+        self.has_header = has_header;
+        self
+    }
+
+    /// Trims each line before parsing.
+    pub fn trim(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self
+    }
+
+    /// Skips blank lines.
+    pub fn skip_blank(mut self, skip_blank: bool) -> Self {
+        self.skip_blank = skip_blank;
+        self
+    }
+
+    /// Sets (or disables, with `None`) the comment prefix.
+    pub fn comment_prefix(mut self, prefix: Option<&str>) -> Self {
+        self.comment_prefix = prefix.map(str::to_string);
+        self
+    }
+
+    /// Returns true if the line must be ignored.
+    fn skip(&self, line: &str) -> bool {
+        let content = line.trim_start();
+        (self.skip_blank && content.is_empty())
+            || self
+                .comment_prefix
+                .as_deref()
+                .is_some_and(|prefix| content.starts_with(prefix))
+    }
+}
+
+/// A line that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadLinesError {
+    /// The file being read.
+    pub path: PathBuf,
+    /// The line number, starting at 1.
+    pub line_number: usize,
+    /// The original text of the line.
+    pub line: String,
+    /// The parser error message.
+    pub message: String,
+}
+
+impl Display for ReadLinesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {:?}",
+            self.path.display(),
+            self.line_number,
+            self.message,
+            self.line
+        )
+    }
+}
+
+impl Error for ReadLinesError {}
+
+/**
+Reads a file into any collection, parsing one item per line.
+
+Uses the default [`ReadOptions`]: lines are trimmed, blank lines and lines
+starting with `#` are skipped. The file encoding is detected
+(see [`open_file_decoded`]).
+
+### Errors
+Returns an I/O error, or a [`ReadLinesError`] with the line number and
+the original text of the first line that cannot be parsed.
+
+Example:
+```
+    use claudiofsr_lib::{BTreeSetExtension, read_lines_into};
+    use std::collections::BTreeSet;
+
+    let path = "/tmp/read_lines_example.txt";
+    let keys: BTreeSet<u64> = BTreeSet::from([30, 10, 20]);
+    keys.write_to_file(path).unwrap();
+
+    let read: BTreeSet<u64> = read_lines_into::<BTreeSet<u64>, u64>(path).unwrap();
+    assert_eq!(read, keys);
+
+    std::fs::write(path, "1\n# comment\n\n2\nthree\n").unwrap();
+    let error = read_lines_into::<Vec<u64>, u64>(path).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "/tmp/read_lines_example.txt:5: invalid digit found in string: \"three\""
+    );
+
+    std::fs::remove_file(path).unwrap();
+```
+*/
+pub fn read_lines_into<C, T>(path: impl AsRef<Path>) -> Result<C>
+where
+    C: FromIterator<T>,
+    T: FromStr,
+    T::Err: Display,
+{
+    read_lines_into_with(path, &ReadOptions::default())
+}
+
+/// Reads a file into any collection, parsing one item per line according to `options`.
+///
+/// See [`read_lines_into`].
+pub fn read_lines_into_with<C, T>(path: impl AsRef<Path>, options: &ReadOptions) -> Result<C>
+where
+    C: FromIterator<T>,
+    T: FromStr,
+    T::Err: Display,
+{
+    parse_lines(path.as_ref(), options, |text| {
+        text.parse::<T>().map_err(|error| error.to_string())
+    })
+}
+
+/**
+Reads a file of `key<separator>value` lines into any collection of pairs,
+such as `HashMap` or `BTreeMap`.
+
+This is the inverse of [`WriteLines`](crate::WriteLines) for maps.
+
+Example:
+```
+    use claudiofsr_lib::{ReadOptions, WriteLines, WriteOptions, read_pairs_into};
+    use std::collections::BTreeMap;
+
+    let path = "/tmp/read_pairs_example.txt";
+    let map: BTreeMap<u16, String> = BTreeMap::from([(50, "revenda".into()), (51, "insumos".into())]);
+
+    map.write_lines(path, &WriteOptions::default().separator(";")).unwrap();
+
+    let options = ReadOptions::default().separator(";");
+    let read: BTreeMap<u16, String> = read_pairs_into(path, &options).unwrap();
+    assert_eq!(read, map);
+
+    std::fs::remove_file(path).unwrap();
+```
+*/
+pub fn read_pairs_into<C, K, V>(path: impl AsRef<Path>, options: &ReadOptions) -> Result<C>
+where
+    C: FromIterator<(K, V)>,
+    K: FromStr,
+    K::Err: Display,
+    V: FromStr,
+    V::Err: Display,
+{
+    let separator = options.separator.as_str();
+
+    parse_lines(path.as_ref(), options, |text| {
+        let (key, value) = text
+            .split_once(separator)
+            .ok_or_else(|| format!("separator {separator:?} not found"))?;
+
+        let (key, value) = if options.trim {
+            (key.trim(), value.trim())
+        } else {
+            (key, value)
+        };
+
+        let key = key.parse::<K>().map_err(|error| format!("key: {error}"))?;
+        let value = value
+            .parse::<V>()
+            .map_err(|error| format!("value: {error}"))?;
+
+        Ok((key, value))
+    })
+}
+
+/// Parses every relevant line of the file with `parse`.
+fn parse_lines<C, T, F>(path: &Path, options: &ReadOptions, parse: F) -> Result<C>
+where
+    C: FromIterator<T>,
+    F: Fn(&str) -> Result<T, String>,
+{
+    let reader = open_file_decoded(path)?;
+    let skip_header = usize::from(options.has_header);
+
+    reader
+        .lines()
+        .enumerate()
+        .skip(skip_header)
+        .filter_map(|(index, line)| {
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => return Some(Err(crate::Error::io(path, error))),
+            };
+
+            if options.skip(&line) {
+                return None;
+            }
+
+            let text = if options.trim { line.trim() } else { &line };
+
+            let result = parse(text).map_err(|message| {
+                ReadLinesError {
+                    path: path.to_path_buf(),
+                    line_number: index + 1,
+                    line: line.clone(),
+                    message,
+                }
+                .into()
+            });
+
+            Some(result)
+        })
+        .collect()
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output read_lines_tests
+
+#[cfg(test)]
+mod read_lines_tests {
+    use super::*;
+    use crate::{HashSetExtension, WriteLines, WriteOptions};
+    use std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        fs,
+    };
+
+    #[test]
+    fn round_trip_sets_and_vectors() -> Result<()> {
+        let path = "/tmp/read_lines_round_trip.txt";
+
+        let set: HashSet<String> = HashSet::from(["12345678000190".into(), "abc def".into()]);
+        set.write_to_file(path)?;
+        let read: HashSet<String> = read_lines_into::<HashSet<String>, String>(path)?;
+        assert_eq!(read, set);
+
+        let values: Vec<f64> = vec![1.5, -2.25, 1e10];
+        let options = WriteOptions::default().header("# valores");
+        values.write_lines(path, &options)?;
+        let read = read_lines_into::<Vec<f64>, f64>(path)?;
+        assert_eq!(read, values);
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip_maps_with_header() -> Result<()> {
+        let path = "/tmp/read_pairs_round_trip.txt";
+
+        let map: HashMap<String, u32> = HashMap::from([("C100".into(), 12), ("C170".into(), 40)]);
+        let options = WriteOptions::default()
+            .separator("|")
+            .header("registro|quantidade")
+            .sorted(true);
+        map.write_lines(path, &options)?;
+
+        let options = ReadOptions::default().separator("|").has_header(true);
+        let read: HashMap<String, u32> = read_pairs_into(path, &options)?;
+        assert_eq!(read, map);
+
+        fs::write(path, "a|1\nb\n")?;
+        let error = read_pairs_into::<BTreeMap<String, u32>, _, _>(path, &options).unwrap_err();
+        println!("error: {error}");
+        assert!(
+            error
+                .to_string()
+                .ends_with(":2: separator \"|\" not found: \"b\"")
+        );
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn options_control_trimming_and_skipping() -> Result<()> {
+        let path = "/tmp/read_lines_options.txt";
+        fs::write(path, "  a  \n\n; note\n#b\n")?;
+
+        let read: Vec<String> = read_lines_into::<Vec<String>, String>(path)?;
+        assert_eq!(read, ["a", "; note"]);
+
+        let options = ReadOptions::default()
+            .trim(false)
+            .skip_blank(false)
+            .comment_prefix(Some(";"));
+        let read: Vec<String> = read_lines_into_with(path, &options)?;
+        assert_eq!(read, ["  a  ", "", "#b"]);
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn error_reports_line_number_and_text() -> Result<()> {
+        let path = "/tmp/read_lines_error.txt";
+        fs::write(path, "10\n 20 \n2x0\n")?;
+
+        let error = read_lines_into::<Vec<u8>, u8>(path).unwrap_err();
+        let error = error.downcast_ref::<ReadLinesError>().unwrap();
+        assert_eq!(error.line_number, 3);
+        assert_eq!(error.line, "2x0");
+
+        fs::remove_file(path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+//! # Reverse Line Reader
+//!
+//! Reads a file backwards in blocks, yielding lines from the end,
+//! without reading the whole file.
+
+use crate::{Encoding, Error, FileExtension, Result};
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+/// Default size of the blocks read by [`ReverseLines`].
+pub const REVERSE_LINES_BLOCK_SIZE: usize = 64 * 1024;
+
+/**
+Iterator over the lines of a reader, from the last line to the first.
+
+Each line is returned as bytes, without `\n` or `\r\n`
+(a `\r` not followed by `\n` is kept).
+A trailing newline at the end of the file does not produce an empty line,
+and a last line without a trailing newline is returned normally, so the
+lines are the same as those of `BufRead::lines`, in reverse order.
+
+The bytes are not decoded (a BOM is kept at the start of the first line),
+so this is meant for UTF-8 and single-byte encodings, not UTF-16.
+See [`tail`] for decoded lines.
+
+Example:
+```
+    use claudiofsr_lib::ReverseLines;
+
+    let path = "/tmp/reverse_lines_example.txt";
+    std::fs::write(path, "|0000|017|\r\n|C100|0|\r\n|9999|3|\r\n").unwrap();
+
+    let mut lines = ReverseLines::open(path).unwrap();
+
+    assert_eq!(lines.next().unwrap().unwrap(), b"|9999|3|");
+    assert_eq!(lines.next().unwrap().unwrap(), b"|C100|0|");
+    assert_eq!(lines.next().unwrap().unwrap(), b"|0000|017|");
+    assert!(lines.next().is_none());
+
+    std::fs::remove_file(path).unwrap();
+```
+*/
+#[derive(Debug)]
+pub struct ReverseLines<R> {
+    reader: R,
+    /// Start of the bytes already read (everything before it is unread).
+    position: u64,
+    /// Blocks read but not yet returned, from `position`, in file order.
+    /// Only the first block may contain a newline.
+    pending: VecDeque<Vec<u8>>,
+    block_size: usize,
+    /// True until the trailing newline of the file has been checked.
+    at_end: bool,
+    /// Whether the next line returned was terminated by `\n`
+    /// (false only for a last line without a trailing newline).
+    terminated: bool,
+    finished: bool,
+}
+
+impl ReverseLines<File> {
+    /// Opens a file for reverse reading. Errors include the path.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        File::open(path)
+            .and_then(ReverseLines::new)
+            .map_err(|error| Error::io(path, error))
+    }
+}
+
+impl<R: Read + Seek> ReverseLines<R> {
+    /// Creates the iterator, starting from the end of `reader`.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let position = reader.seek(SeekFrom::End(0))?;
+        Ok(ReverseLines {
+            reader,
+            position,
+            pending: VecDeque::new(),
+            block_size: REVERSE_LINES_BLOCK_SIZE,
+            at_end: true,
+            terminated: true,
+            finished: position == 0,
+        })
+    }
+
+    /// Sets the size of the blocks read (at least 1).
+    pub fn block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size.max(1);
+        self
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the block before `position` and prepends it to `pending`.
+    fn read_previous_block(&mut self) -> io::Result<()> {
+        let len = self.position.min(self.block_size as u64);
+        self.position -= len;
+
+        let mut block = vec![0; len as usize];
+        self.reader.seek(SeekFrom::Start(self.position))?;
+        self.reader.read_exact(&mut block)?;
+
+        self.pending.push_front(block);
+        Ok(())
+    }
+
+    /// Joins `first` and the pending blocks after the first one into a line.
+    fn take_line(&mut self, first: Vec<u8>) -> Vec<u8> {
+        let mut line = first;
+        line.reserve(self.pending.iter().skip(1).map(Vec::len).sum());
+        self.pending
+            .drain(1..)
+            .for_each(|block| line.extend_from_slice(&block));
+
+        if std::mem::replace(&mut self.terminated, true) && line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        line
+    }
+
+    fn next_line(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        // The newline at the end of the file terminates the last line.
+        if self.at_end {
+            self.read_previous_block()?;
+            self.terminated = self.pending[0].last() == Some(&b'\n');
+            if self.terminated {
+                self.pending[0].pop();
+            }
+            self.at_end = false;
+        }
+
+        loop {
+            let first = &mut self.pending[0];
+            if let Some(index) = first.iter().rposition(|&b| b == b'\n') {
+                let start = first.split_off(index + 1);
+                first.truncate(index);
+                return Ok(Some(self.take_line(start)));
+            }
+
+            if self.position == 0 {
+                self.finished = true;
+                let first = std::mem::take(&mut self.pending[0]);
+                return Ok(Some(self.take_line(first)));
+            }
+
+            self.read_previous_block()?;
+        }
+    }
+}
+
+impl<R: Read + Seek> Iterator for ReverseLines<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_line() {
+            Ok(line) => line.map(Ok),
+            Err(error) => {
+                self.finished = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/**
+Returns the last `n` lines of a file, in file order.
+
+The encoding is detected from the start of the file (see [`Encoding::detect`])
+and the lines are decoded to UTF-8.
+
+### Errors
+Returns [`Error::Io`] (with the path), or [`Error::Encoding`] for UTF-16 files.
+
+Example:
+```
+    use claudiofsr_lib::tail;
+
+    let path = "/tmp/tail_example.txt";
+    std::fs::write(path, b"|0000|Relat\xF3rio|\n|C100|0|\n|9999|3|").unwrap();
+
+    assert_eq!(tail(path, 2).unwrap(), ["|C100|0|", "|9999|3|"]);
+    assert_eq!(tail(path, 5).unwrap(), ["|0000|Relatório|", "|C100|0|", "|9999|3|"]);
+    assert!(tail(path, 0).unwrap().is_empty());
+
+    std::fs::remove_file(path).unwrap();
+```
+*/
+pub fn tail<P: AsRef<Path>>(path: P, n: usize) -> Result<Vec<String>> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|error| Error::io(path, error))?;
+
+    let encoding = file
+        .detect_encoding()
+        .map_err(|error| Error::io(path, error))?;
+
+    if matches!(encoding, Encoding::Utf16Le | Encoding::Utf16Be) {
+        let msg = format!("{}: tail does not support {encoding:?}", path.display());
+        return Err(Error::Encoding(msg));
+    }
+
+    let mut lines = ReverseLines::new(file)
+        .map_err(|error| Error::io(path, error))?
+        .take(n)
+        .map(|line| line.map(|line| encoding.decode(&line).into_owned()))
+        .collect::<io::Result<Vec<String>>>()
+        .map_err(|error| Error::io(path, error))?;
+
+    lines.reverse();
+    Ok(lines)
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output reverse_lines_tests
+
+#[cfg(test)]
+mod reverse_lines_tests {
+    use super::*;
+    use std::io::{BufRead, Cursor};
+
+    fn reversed(content: &str, block_size: usize) -> Vec<String> {
+        ReverseLines::new(Cursor::new(content))
+            .unwrap()
+            .block_size(block_size)
+            .map(|line| String::from_utf8(line.unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn same_lines_as_bufread_in_reverse() {
+        let contents = [
+            "",
+            "\n",
+            "\n\n",
+            "a",
+            "a\n",
+            "a\r\nb\r\n",
+            "a\r\nb",
+            "first\n\nthird\r\n\r\nfifth",
+            "no\rcr\r\r\n",
+            "a\r",
+            "a\r\nb\r",
+        ];
+
+        for content in contents {
+            let mut expected: Vec<String> =
+                Cursor::new(content).lines().map(Result::unwrap).collect();
+            expected.reverse();
+
+            for block_size in [1, 2, 3, 5, 64] {
+                assert_eq!(
+                    reversed(content, block_size),
+                    expected,
+                    "content: {content:?}, block_size: {block_size}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn large_file_from_the_end() -> Result<()> {
+        let path = "/tmp/reverse_lines_tests_large.txt";
+        let content: String = (1..=100_000).map(|i| format!("|C170|{i}|\r\n")).collect();
+        std::fs::write(path, content)?;
+
+        let last: Vec<String> = tail(path, 3)?;
+        assert_eq!(last, ["|C170|99998|", "|C170|99999|", "|C170|100000|"]);
+
+        let count = ReverseLines::open(path)?.count();
+        assert_eq!(count, 100_000);
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn tail_decodes_bom_and_rejects_utf16() -> Result<()> {
+        let path = "/tmp/reverse_lines_tests_bom.txt";
+
+        std::fs::write(path, "\u{FEFF}único\n")?;
+        assert_eq!(tail(path, 10)?, ["único"]);
+
+        std::fs::write(path, b"\xFF\xFEa\x00\n\x00")?;
+        let error = tail(path, 1).unwrap_err();
+        assert!(matches!(error, Error::Encoding(_)));
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+}
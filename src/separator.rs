@@ -8,6 +8,7 @@ use rust_decimal::{Decimal, RoundingStrategy};
 // ============================================================================
 
 /// Defines localized formatting styles for separators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FormatStyle {
     /// 1.234,56 (Common in Europe and South America)
     Euro,
@@ -15,6 +16,19 @@ pub enum FormatStyle {
     PtBr,
     /// 1,234.56 (Standard in US, UK, and International science)
     Us,
+    /// 1234,56 (No thousands grouping, as required by SPED EFD files)
+    Sped,
+}
+
+impl FormatStyle {
+    /// Returns the thousands separator (if any) and the decimal separator.
+    pub fn separators(self) -> (Option<char>, &'static str) {
+        match self {
+            FormatStyle::Euro | FormatStyle::PtBr => (Some('.'), ","),
+            FormatStyle::Us => (Some(','), "."),
+            FormatStyle::Sped => (None, ","),
+        }
+    }
 }
 
 /// Unifies numeric types and provides a high-performance writing interface.
@@ -121,6 +135,10 @@ and applies formatting based on the selected `FormatStyle`.
     // US format: Comma for thousands, Dot for decimals
     let us = thousands_separator(number, 3, FormatStyle::Us);
     assert_eq!(us, "1,234,567.895");
+
+    // SPED format: No grouping, Comma for decimals
+    let sped = thousands_separator(number, 2, FormatStyle::Sped);
+    assert_eq!(sped, "1234567,90");
 ```
 */
 pub fn thousands_separator<T: FormattableNumber>(
@@ -138,10 +156,7 @@ pub fn thousands_separator<T: FormattableNumber>(
     let _ = value.write_abs(decimals, &mut abs_temp);
 
     // C. Selection of localized separators.
-    let (thousands_sep, decimal_sep) = match style {
-        FormatStyle::Euro | FormatStyle::PtBr => ('.', ","),
-        FormatStyle::Us => (',', "."),
-    };
+    let (thousands_sep, decimal_sep) = style.separators();
 
     // D. Logic to split integer and fraction.
     // split_once is O(n) and returns references (&str), creating no new strings.
@@ -154,9 +169,10 @@ pub fn thousands_separator<T: FormattableNumber>(
     let is_neg = value.is_negative_num();
     // (len - 1) / 3 gives the exact number of separators needed.
     let num_seps = integer_part.len().saturating_sub(1) / 3;
+    let sep_bytes = thousands_sep.map_or(0, char::len_utf8);
 
     // We sum: raw_len + (seps * sep_bytes) + (1 if negative).
-    let final_capacity = abs_temp.len() + (num_seps * thousands_sep.len_utf8()) + (is_neg as usize);
+    let final_capacity = abs_temp.len() + (num_seps * sep_bytes) + (is_neg as usize);
 
     // F. SINGLE ALLOCATION: Allocate the exact amount of RAM needed.
     let mut result = String::with_capacity(final_capacity);
@@ -166,7 +182,10 @@ pub fn thousands_separator<T: FormattableNumber>(
         result.push('-');
     }
 
-    add_sep(integer_part, thousands_sep, &mut result);
+    match thousands_sep {
+        Some(sep) => add_sep(integer_part, sep, &mut result),
+        None => result.push_str(integer_part),
+    }
 
     if let Some(f) = fraction_part {
         result.push_str(decimal_sep);
@@ -237,6 +256,13 @@ mod separator_tests {
         println!("f64: {val_us}");
         println!("us result: {result}\n");
         assert_eq!(result, "1,234,567.89");
+
+        // Test SPED style (no grouping)
+        let val_sped = -1234567.8912;
+        let result = thousands_separator(val_sped, 2, FormatStyle::Sped);
+        println!("f64: {val_sped}");
+        println!("sped result: {result}\n");
+        assert_eq!(result, "-1234567,89");
     }
 
     /// New tests for Option<T> types
@@ -0,0 +1,399 @@
+//! # SPED EFD Writer
+//!
+//! Writes SPED EFD files (ICMS/IPI and Contribuições) record by record,
+//! computing the block-closing registers (`x990`) and the whole block 9
+//! (`9001`, `9900`, `9990` and `9999`) automatically.
+
+use crate::{Error, FormatStyle, FormattableNumber, Result, thousands_separator};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    io::Write,
+};
+
+/// Line terminator required by the SPED EFD layout.
+const CRLF: &str = "\r\n";
+
+/**
+Formats a number as a SPED EFD decimal field.
+
+SPED fields use a comma as the decimal separator and no thousands grouping.
+
+Example:
+```
+    use claudiofsr_lib::sped_decimal;
+
+    assert_eq!(sped_decimal(1234567.891, 2), "1234567,89");
+    assert_eq!(sped_decimal(-0.5, 4), "-0,5000");
+    assert_eq!(sped_decimal(None::<f64>, 2), "0,00");
+```
+*/
+pub fn sped_decimal<T: FormattableNumber>(value: T, decimals: usize) -> String {
+    thousands_separator(value, decimals, FormatStyle::Sped)
+}
+
+/**
+Replaces the characters forbidden in SPED EFD fields by a space.
+
+The pipe `|` is the field delimiter and line breaks end the record,
+so neither may appear inside a field.
+
+Example:
+```
+    use claudiofsr_lib::sped_escape;
+
+    assert_eq!(sped_escape("ABC|DEF\r\nGHI"), "ABC DEF  GHI");
+    assert_eq!(sped_escape("Cláudio"), "Cláudio");
+```
+*/
+pub fn sped_escape(field: &str) -> Cow<'_, str> {
+    if field.contains(is_forbidden) {
+        Cow::Owned(field.replace(is_forbidden, " "))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Characters that cannot appear inside a SPED EFD field.
+fn is_forbidden(ch: char) -> bool {
+    matches!(ch, '|' | '\r' | '\n')
+}
+
+/**
+SPED EFD file writer with automatic closing and totalizer registers.
+
+Records must be written grouped by block (the first character of the register),
+in the order they should appear in the file.
+
+The writer then:
+- closes each block with its `x990` register (`QTD_LIN_x`);
+- appends block 9: `9001`, one `9900` register per register type,
+  `9990` (`QTD_LIN_9`) and `9999` (`QTD_LIN`).
+
+Fields are validated: pipes and line breaks are rejected (see [`sped_escape`]).
+
+Example:
+```
+    use claudiofsr_lib::{SpedWriter, sped_decimal};
+
+    let mut sped = SpedWriter::new(Vec::new());
+
+    sped.write_record("0000", ["006", "0", "01012024", "31012024", "EMPRESA"]).unwrap();
+    sped.write_record("0001", ["0"]).unwrap();
+    sped.write_record("C001", ["0"]).unwrap();
+    sped.write_record("C100", ["0", "1", &sped_decimal(1500.0, 2)]).unwrap();
+
+    let bytes: Vec<u8> = sped.finish().unwrap();
+    let text = String::from_utf8(bytes).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(lines[0], "|0000|006|0|01012024|31012024|EMPRESA|");
+    assert_eq!(lines[2], "|0990|3|");
+    assert_eq!(lines[5], "|C990|3|");
+    assert_eq!(lines.last(), Some(&"|9999|19|"));
+```
+*/
+pub struct SpedWriter<W: Write> {
+    writer: W,
+    /// The block currently open and the number of lines written to it.
+    block: Option<(char, u64)>,
+    /// Blocks already closed with their `x990` register.
+    closed: HashSet<char>,
+    /// Register counts in order of first appearance.
+    registers: Vec<(String, u64)>,
+    /// Index of each register in `registers`.
+    positions: HashMap<String, usize>,
+    total_lines: u64,
+}
+
+impl<W: Write> SpedWriter<W> {
+    /// Creates a new writer.
+    pub fn new(writer: W) -> Self {
+        SpedWriter {
+            writer,
+            block: None,
+            closed: HashSet::new(),
+            registers: Vec::new(),
+            positions: HashMap::new(),
+            total_lines: 0,
+        }
+    }
+
+    /// Returns the number of lines written so far.
+    pub fn lines_written(&self) -> u64 {
+        self.total_lines
+    }
+
+    /// Writes one record `|REG|field_1|...|field_n|`.
+    ///
+    /// Closes the previous block when the register belongs to a new block.
+    ///
+    /// ### Errors
+    /// Returns [`Error::InvalidDocument`] if the register code is invalid, belongs to block 9 or
+    /// is a closing register (`x990`), if its block was already closed,
+    /// or if any field contains a pipe or a line break.
+    pub fn write_record<I, S>(&mut self, register: &str, fields: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let block = validate_register(register)?;
+
+        if block == '9' || register.ends_with("990") {
+            let msg = format!("register {register} is computed automatically by SpedWriter");
+            return Err(Error::InvalidDocument(msg));
+        }
+
+        let new_block = self.block.is_none_or(|(current, _)| current != block);
+        if new_block && self.closed.contains(&block) {
+            let msg = format!("block {block} was already closed (register {register})");
+            return Err(Error::InvalidDocument(msg));
+        }
+
+        // Fields are checked before closing the previous block,
+        // so a rejected record leaves the writer unchanged.
+        let mut line = format!("|{register}|");
+        for (index, field) in fields.into_iter().enumerate() {
+            let field = field.as_ref();
+            if field.contains(is_forbidden) {
+                let msg = format!(
+                    "register {register}, field {}: pipes and line breaks are not allowed: {field:?}",
+                    index + 2
+                );
+                return Err(Error::InvalidDocument(msg));
+            }
+            line.push_str(field);
+            line.push('|');
+        }
+
+        if new_block {
+            self.close_block()?;
+            self.block = Some((block, 0));
+        }
+
+        self.write_line(register, &line)
+    }
+
+    /// Closes the last block, writes block 9 and returns the inner writer.
+    pub fn finish(mut self) -> Result<W> {
+        self.close_block()?;
+
+        // 9001 + one 9900 per register type (including 9001, 9900, 9990 and 9999) + 9990 + 9999
+        self.count("9001");
+        self.count("9990");
+        self.count("9999");
+        let distinct = self.registers.len() as u64 + 1; // + 9900 itself
+        self.count_n("9900", distinct);
+
+        self.write_raw("|9001|0|")?;
+
+        let totals: Vec<String> = self
+            .registers
+            .iter()
+            .map(|(register, count)| format!("|9900|{register}|{count}|"))
+            .collect();
+
+        for line in &totals {
+            self.write_raw(line)?;
+        }
+
+        let lines_block_9 = 1 + distinct + 2;
+        self.write_raw(&format!("|9990|{lines_block_9}|"))?;
+
+        let total = self.total_lines + 1;
+        self.write_raw(&format!("|9999|{total}|"))?;
+
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    /// Writes the `x990` register of the open block, if any.
+    fn close_block(&mut self) -> Result<()> {
+        if let Some((block, lines)) = self.block.take() {
+            let register = format!("{block}990");
+            // The closing register counts itself.
+            let line = format!("|{register}|{}|", lines + 1);
+            self.write_line(&register, &line)?;
+            self.closed.insert(block);
+        }
+        Ok(())
+    }
+
+    /// Writes a line and updates the block and register counters.
+    fn write_line(&mut self, register: &str, line: &str) -> Result<()> {
+        self.write_raw(line)?;
+        self.count(register);
+        if let Some((_, lines)) = self.block.as_mut() {
+            *lines += 1;
+        }
+        Ok(())
+    }
+
+    /// Writes a line without touching the register counters.
+    fn write_raw(&mut self, line: &str) -> Result<()> {
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(CRLF.as_bytes())?;
+        self.total_lines += 1;
+        Ok(())
+    }
+
+    fn count(&mut self, register: &str) {
+        self.count_n(register, 1);
+    }
+
+    fn count_n(&mut self, register: &str, n: u64) {
+        match self.positions.get(register) {
+            Some(&index) => self.registers[index].1 += n,
+            None => {
+                self.positions
+                    .insert(register.to_string(), self.registers.len());
+                self.registers.push((register.to_string(), n));
+            }
+        }
+    }
+}
+
+/// Checks the register code (4 uppercase ASCII alphanumeric characters)
+/// and returns its block.
+fn validate_register(register: &str) -> Result<char> {
+    let valid = register.len() == 4
+        && register
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b.is_ascii_uppercase());
+
+    match register.chars().next() {
+        Some(block) if valid => Ok(block),
+        _ => {
+            let msg = format!("invalid SPED register code: {register:?}");
+            Err(Error::InvalidDocument(msg))
+        }
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output sped_tests
+
+#[cfg(test)]
+mod sped_tests {
+    use super::*;
+
+    fn render(records: &[(&str, &[&str])]) -> Result<Vec<String>> {
+        let mut sped = SpedWriter::new(Vec::new());
+        for (register, fields) in records {
+            sped.write_record(register, fields.iter())?;
+        }
+        let text = String::from_utf8(sped.finish()?)?;
+        Ok(text.split_terminator(CRLF).map(String::from).collect())
+    }
+
+    #[test]
+    fn computes_closing_and_totalizer_registers() -> Result<()> {
+        let lines = render(&[
+            ("0000", &["006", "0"]),
+            ("0001", &["0"]),
+            ("0150", &["A"]),
+            ("0150", &["B"]),
+            ("C001", &["0"]),
+            ("C100", &["1"]),
+            ("C170", &["1", "1500,00"]),
+        ])?;
+
+        for line in &lines {
+            println!("{line}");
+        }
+
+        assert_eq!(
+            lines,
+            [
+                "|0000|006|0|",
+                "|0001|0|",
+                "|0150|A|",
+                "|0150|B|",
+                "|0990|5|",
+                "|C001|0|",
+                "|C100|1|",
+                "|C170|1|1500,00|",
+                "|C990|4|",
+                "|9001|0|",
+                "|9900|0000|1|",
+                "|9900|0001|1|",
+                "|9900|0150|2|",
+                "|9900|0990|1|",
+                "|9900|C001|1|",
+                "|9900|C100|1|",
+                "|9900|C170|1|",
+                "|9900|C990|1|",
+                "|9900|9001|1|",
+                "|9900|9990|1|",
+                "|9900|9999|1|",
+                "|9900|9900|12|",
+                "|9990|15|",
+                "|9999|24|",
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn empty_file_has_only_block_9() -> Result<()> {
+        let lines = render(&[])?;
+        assert_eq!(
+            lines,
+            [
+                "|9001|0|",
+                "|9900|9001|1|",
+                "|9900|9990|1|",
+                "|9900|9999|1|",
+                "|9900|9900|4|",
+                "|9990|7|",
+                "|9999|7|",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_invalid_records() {
+        let mut sped = SpedWriter::new(Vec::new());
+
+        assert!(sped.write_record("C100", ["a|b"]).is_err());
+        assert!(sped.write_record("C100", ["a\nb"]).is_err());
+        assert!(sped.write_record("c100", ["a"]).is_err());
+        assert!(sped.write_record("C10", ["a"]).is_err());
+        assert!(sped.write_record("C990", ["1"]).is_err());
+        assert!(sped.write_record("9900", ["C100", "1"]).is_err());
+
+        // A block cannot be reopened after being closed.
+        assert!(sped.write_record("C100", ["a"]).is_ok());
+        assert!(sped.write_record("D100", ["a"]).is_ok());
+        assert!(sped.write_record("C170", ["a"]).is_err());
+    }
+
+    #[test]
+    fn rejected_record_leaves_writer_unchanged() -> Result<()> {
+        let mut sped = SpedWriter::new(Vec::new());
+        sped.write_record("0000", ["006"])?;
+
+        // Invalid field in a new block: block 0 stays open.
+        assert!(sped.write_record("C100", ["a|b"]).is_err());
+        assert_eq!(sped.lines_written(), 1);
+        assert_eq!(sped.writer, b"|0000|006|\r\n");
+
+        sped.write_record("0001", ["0"])?;
+        let output = String::from_utf8(sped.finish()?).unwrap();
+        assert!(output.starts_with("|0000|006|\r\n|0001|0|\r\n|0990|3|\r\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn decimal_fields_use_comma_without_grouping() {
+        assert_eq!(sped_decimal(1234567.891, 2), "1234567,89");
+        assert_eq!(sped_decimal(0.0, 2), "0,00");
+        assert_eq!(sped_decimal(-12.4, 0), "-12");
+    }
+}
@@ -57,6 +57,27 @@ pub trait StrExtension {
     */
     fn chars_count(&self) -> usize;
 
+    /**
+    Returns the width in terminal columns, ignoring ANSI escape sequences.
+
+    Unlike [`chars_count`](StrExtension::chars_count), wide characters count
+    as 2 and combining marks as 0 (see [`display_width`](crate::display_width)),
+    so it can be used to pad columns.
+    ```
+        use claudiofsr_lib::{Colors, StrExtension};
+
+        let text_a: &str = "Bom dia おはよう!";
+        let text_b: String = "Cláudio".red().enabled(true).to_string();
+        assert_eq!(text_a.chars_count(), 13);
+        assert_eq!(text_a.display_width(), 17);
+        assert_eq!(text_b.display_width(), 7);
+
+        let padding = " ".repeat(20 - text_a.display_width());
+        assert_eq!(format!("{text_a}{padding}|").display_width(), 21);
+    ```
+    */
+    fn display_width(&self) -> usize;
+
     /**
     Counts the number of occurrences of a given character in a String.
     ```
@@ -353,6 +374,10 @@ where
         self.chars().count()
     }
 
+    fn display_width(&self) -> usize {
+        crate::display_width(self)
+    }
+
     fn count_char(&self, ch: char) -> usize {
         self.chars()
             .filter(|current_char| *current_char == ch)
@@ -0,0 +1,612 @@
+//! # ANSI Styles
+//!
+//! Colors and text attributes for terminal output.
+//!
+//! [`Styled`] writes the escape codes lazily in its `Display` implementation,
+//! without allocating, and only if colors are enabled for its stream.
+//!
+//! Colors are enabled automatically (see [`colors_enabled`]), following
+//! <https://no-color.org> and <https://bixense.com/clicolors>:
+//! - `NO_COLOR` set (and not empty): disabled;
+//! - `CLICOLOR_FORCE` set (and not `0`): enabled;
+//! - otherwise, enabled if the stream is a terminal.
+//!
+//! This can be overridden globally or per stream (see [`set_colors_enabled`]).
+
+use std::{
+    env,
+    ffi::OsString,
+    fmt::{self, Display},
+    io::{self, IsTerminal},
+    sync::{
+        OnceLock,
+        atomic::{AtomicU8, Ordering},
+    },
+};
+
+// https://gist.github.com/abritinthebay/d80eb99b2726c83feb0d97eab95206c4
+// https://talyian.github.io/ansicolors/
+
+const RESET: &str = "\x1b[0m";
+
+/// Terminal colors: the 16 standard colors, the 256-color palette, or RGB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// A color of the 256-color palette.
+    Ansi256(u8),
+    /// A 24-bit color (true color).
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    /// Writes the SGR parameters of the color, as foreground or background.
+    fn write_code(self, f: &mut fmt::Formatter, background: bool) -> fmt::Result {
+        let offset = if background { 10 } else { 0 };
+        let code = match self {
+            Color::Black => 30,
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+            Color::BrightBlack => 90,
+            Color::BrightRed => 91,
+            Color::BrightGreen => 92,
+            Color::BrightYellow => 93,
+            Color::BrightBlue => 94,
+            Color::BrightMagenta => 95,
+            Color::BrightCyan => 96,
+            Color::BrightWhite => 97,
+            Color::Ansi256(n) => return write!(f, "{};5;{n}", 38 + offset),
+            Color::Rgb(r, g, b) => return write!(f, "{};2;{r};{g};{b}", 38 + offset),
+        };
+        write!(f, "{}", code + offset)
+    }
+}
+
+// Text attributes (bit flags) and their SGR codes.
+const BOLD: u8 = 1 << 0;
+const DIM: u8 = 1 << 1;
+const ITALIC: u8 = 1 << 2;
+const UNDERLINE: u8 = 1 << 3;
+const REVERSE: u8 = 1 << 4;
+const STRIKETHROUGH: u8 = 1 << 5;
+
+const ATTRIBUTES: [(u8, u8); 6] = [
+    (BOLD, 1),
+    (DIM, 2),
+    (ITALIC, 3),
+    (UNDERLINE, 4),
+    (REVERSE, 7),
+    (STRIKETHROUGH, 9),
+];
+
+/// Generates chainable methods that set a color or an attribute.
+macro_rules! style_methods {
+    ($($(#[$doc:meta])* $name:ident => $field:ident $op:tt $value:expr;)*) => {
+        $(
+            $(#[$doc])*
+            pub const fn $name(mut self) -> Self {
+                style_methods!(@set self, $field $op $value);
+                self
+            }
+        )*
+    };
+    (@set $self:ident, $field:ident = $value:expr) => {
+        $self.$field = Some($value)
+    };
+    (@set $self:ident, $field:ident |= $value:expr) => {
+        $self.$field |= $value
+    };
+}
+
+/// Forwards the chainable methods of [`Style`] to [`Styled`].
+macro_rules! styled_methods {
+    ($($name:ident),*) => {
+        $(
+            #[doc = concat!("See [`Style::", stringify!($name), "`].")]
+            pub const fn $name(mut self) -> Self {
+                self.style = self.style.$name();
+                self
+            }
+        )*
+    };
+}
+
+/**
+A text style: foreground and background colors plus attributes.
+
+Example:
+```
+    use claudiofsr_lib::{Color, Style};
+
+    let warning = Style::new().yellow().bold();
+    let error = Style::new().fg(Color::Rgb(255, 85, 0)).on(Color::Ansi256(236)).underline();
+
+    let text = warning.paint("warning").enabled(true).to_string();
+    assert_eq!(text, "\x1b[1;33mwarning\x1b[0m");
+
+    let text = error.paint(42).enabled(true).to_string();
+    assert_eq!(text, "\x1b[4;38;2;255;85;0;48;5;236m42\x1b[0m");
+
+    // Without colors, only the value is written.
+    assert_eq!(error.paint(42).enabled(false).to_string(), "42");
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    attributes: u8,
+}
+
+impl Style {
+    /// A style without colors or attributes.
+    pub const fn new() -> Self {
+        Style {
+            fg: None,
+            bg: None,
+            attributes: 0,
+        }
+    }
+
+    /// Returns true if the style has no colors or attributes.
+    pub const fn is_plain(&self) -> bool {
+        self.fg.is_none() && self.bg.is_none() && self.attributes == 0
+    }
+
+    /// Sets the foreground color.
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    /// Sets the background color.
+    pub const fn on(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    style_methods! {
+        /// Bold (or increased intensity).
+        bold => attributes |= BOLD;
+        /// Dim (or decreased intensity).
+        dim => attributes |= DIM;
+        /// Italic.
+        italic => attributes |= ITALIC;
+        /// Underline.
+        underline => attributes |= UNDERLINE;
+        /// Swaps the foreground and background colors.
+        reverse => attributes |= REVERSE;
+        /// Strikethrough.
+        strikethrough => attributes |= STRIKETHROUGH;
+        /// Black foreground.
+        black => fg = Color::Black;
+        /// Red foreground.
+        red => fg = Color::Red;
+        /// Green foreground.
+        green => fg = Color::Green;
+        /// Yellow foreground.
+        yellow => fg = Color::Yellow;
+        /// Blue foreground.
+        blue => fg = Color::Blue;
+        /// Magenta foreground.
+        magenta => fg = Color::Magenta;
+        /// Cyan foreground.
+        cyan => fg = Color::Cyan;
+        /// White foreground.
+        white => fg = Color::White;
+        /// Black background.
+        on_black => bg = Color::Black;
+        /// Red background.
+        on_red => bg = Color::Red;
+        /// Green background.
+        on_green => bg = Color::Green;
+        /// Yellow background.
+        on_yellow => bg = Color::Yellow;
+        /// Blue background.
+        on_blue => bg = Color::Blue;
+        /// Magenta background.
+        on_magenta => bg = Color::Magenta;
+        /// Cyan background.
+        on_cyan => bg = Color::Cyan;
+        /// White background.
+        on_white => bg = Color::White;
+    }
+
+    /// Wraps `value` with this style, for stdout.
+    pub const fn paint<T>(self, value: T) -> Styled<T> {
+        Styled {
+            value,
+            style: self,
+            stream: Stream::Stdout,
+            enabled: None,
+        }
+    }
+
+    /// Writes the escape sequence that starts the style (nothing if plain).
+    pub fn write_prefix(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_plain() {
+            return Ok(());
+        }
+
+        f.write_str("\x1b[")?;
+        let mut first = true;
+        let mut separator = |f: &mut fmt::Formatter| {
+            let result = if first { Ok(()) } else { f.write_str(";") };
+            first = false;
+            result
+        };
+
+        for (flag, code) in ATTRIBUTES {
+            if self.attributes & flag != 0 {
+                separator(f)?;
+                write!(f, "{code}")?;
+            }
+        }
+        if let Some(color) = self.fg {
+            separator(f)?;
+            color.write_code(f, false)?;
+        }
+        if let Some(color) = self.bg {
+            separator(f)?;
+            color.write_code(f, true)?;
+        }
+
+        f.write_str("m")
+    }
+}
+
+/// Output streams, each with its own color setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    fn index(self) -> usize {
+        match self {
+            Stream::Stdout => 0,
+            Stream::Stderr => 1,
+        }
+    }
+
+    /// Returns true if the stream is a terminal.
+    pub fn is_terminal(self) -> bool {
+        match self {
+            Stream::Stdout => io::stdout().is_terminal(),
+            Stream::Stderr => io::stderr().is_terminal(),
+        }
+    }
+}
+
+// Overrides: AUTO (detect), ON or OFF.
+const AUTO: u8 = 0;
+const ON: u8 = 1;
+const OFF: u8 = 2;
+
+static GLOBAL_COLORS: AtomicU8 = AtomicU8::new(AUTO);
+static STREAM_COLORS: [AtomicU8; 2] = [AtomicU8::new(AUTO), AtomicU8::new(AUTO)];
+static DETECTED_COLORS: [OnceLock<bool>; 2] = [OnceLock::new(), OnceLock::new()];
+
+fn to_override(enabled: Option<bool>) -> u8 {
+    match enabled {
+        None => AUTO,
+        Some(true) => ON,
+        Some(false) => OFF,
+    }
+}
+
+/**
+Enables or disables colors for all streams.
+
+`None` restores automatic detection. A setting for a single
+stream (see [`set_stream_colors_enabled`]) takes precedence.
+*/
+pub fn set_colors_enabled(enabled: Option<bool>) {
+    GLOBAL_COLORS.store(to_override(enabled), Ordering::Relaxed);
+}
+
+/// Enables or disables colors for `stream` (`None` restores the global setting).
+pub fn set_stream_colors_enabled(stream: Stream, enabled: Option<bool>) {
+    STREAM_COLORS[stream.index()].store(to_override(enabled), Ordering::Relaxed);
+}
+
+/// Returns true if styles are written to `stream`.
+///
+/// Automatic detection reads the environment and the terminal once per stream.
+pub fn colors_enabled(stream: Stream) -> bool {
+    let setting = effective_setting(
+        STREAM_COLORS[stream.index()].load(Ordering::Relaxed),
+        GLOBAL_COLORS.load(Ordering::Relaxed),
+    );
+
+    match setting {
+        ON => true,
+        OFF => false,
+        _ => *DETECTED_COLORS[stream.index()].get_or_init(|| {
+            detect_colors(
+                env::var_os("NO_COLOR"),
+                env::var_os("CLICOLOR_FORCE"),
+                stream.is_terminal(),
+            )
+        }),
+    }
+}
+
+/// The stream setting takes precedence over the global one, unless it is `AUTO`.
+fn effective_setting(stream: u8, global: u8) -> u8 {
+    match stream {
+        AUTO => global,
+        setting => setting,
+    }
+}
+
+/// Applies `NO_COLOR`, then `CLICOLOR_FORCE`, then the terminal check.
+fn detect_colors(
+    no_color: Option<OsString>,
+    clicolor_force: Option<OsString>,
+    is_terminal: bool,
+) -> bool {
+    if no_color.is_some_and(|value| !value.is_empty()) {
+        return false;
+    }
+    if clicolor_force.is_some_and(|value| !value.is_empty() && value != "0") {
+        return true;
+    }
+    is_terminal
+}
+
+/**
+A value with a [`Style`], written with escape codes by `Display`.
+
+Nothing is formatted until the value is displayed, and the escape codes
+are written only if colors are enabled for the stream (stdout by default).
+Width and alignment flags apply to the value, not to the escape codes.
+
+Example:
+```
+    use claudiofsr_lib::{Colors, Stream};
+
+    let total = 1234.5.style().green().bold().enabled(true);
+    assert_eq!(format!("{total:>8.2}"), "\x1b[1;32m 1234.50\x1b[0m");
+
+    let message = "done".green().for_stream(Stream::Stderr);
+    eprintln!("{message}");
+```
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Styled<T> {
+    value: T,
+    style: Style,
+    stream: Stream,
+    enabled: Option<bool>,
+}
+
+impl<T> Styled<T> {
+    /// Returns the style.
+    pub const fn get_style(&self) -> Style {
+        self.style
+    }
+
+    /// Replaces the style.
+    pub const fn with_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Follows the color setting of `stream` (stdout by default).
+    pub const fn for_stream(mut self, stream: Stream) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    /// Writes the escape codes or not, ignoring the color settings.
+    pub const fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = Some(enabled);
+        self
+    }
+
+    /// Sets the foreground color.
+    pub const fn fg(mut self, color: Color) -> Self {
+        self.style = self.style.fg(color);
+        self
+    }
+
+    /// Sets the background color.
+    pub const fn on(mut self, color: Color) -> Self {
+        self.style = self.style.on(color);
+        self
+    }
+
+    styled_methods!(
+        bold,
+        dim,
+        italic,
+        underline,
+        reverse,
+        strikethrough,
+        black,
+        red,
+        green,
+        yellow,
+        blue,
+        magenta,
+        cyan,
+        white,
+        on_black,
+        on_red,
+        on_green,
+        on_yellow,
+        on_blue,
+        on_magenta,
+        on_cyan,
+        on_white
+    );
+
+    fn is_enabled(&self) -> bool {
+        !self.style.is_plain() && self.enabled.unwrap_or_else(|| colors_enabled(self.stream))
+    }
+}
+
+impl<T: Display> Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.is_enabled() {
+            return self.value.fmt(f);
+        }
+
+        self.style.write_prefix(f)?;
+        self.value.fmt(f)?;
+        f.write_str(RESET)
+    }
+}
+
+/**
+Styles for any `Display` value.
+
+`style()` starts an empty style; the shortcuts apply one color or attribute.
+Every method returns a [`Styled`], so they can be chained.
+
+Since 0.21 the shortcuts return a [`Styled`] instead of a `String`:
+call `.to_string()` where a `String` is needed.
+The result borrows `self`, so it cannot outlive a temporary:
+`let s = format!("{n}").red();` no longer compiles
+(use `format!("{n}").red().to_string()`, or keep the `String` in a variable).
+
+Example:
+```
+    use claudiofsr_lib::Colors;
+
+    let name = String::from("EFD Contribuições");
+
+    assert_eq!(name.bold().red().enabled(true).to_string(), "\x1b[1;31mEFD Contribuições\x1b[0m");
+    assert_eq!("x".style().blue().underline().enabled(false).to_string(), "x");
+```
+*/
+pub trait Colors: Display {
+    /// Wraps the value with an empty style, to be chained.
+    fn style(&self) -> Styled<&Self> {
+        Style::new().paint(self)
+    }
+
+    /// Wraps the value in bold (see [`Style::bold`]).
+    fn bold(&self) -> Styled<&Self> {
+        self.style().bold()
+    }
+
+    /// Wraps the value in red foreground (see [`Style::red`]).
+    fn red(&self) -> Styled<&Self> {
+        self.style().red()
+    }
+
+    /// Wraps the value in green foreground (see [`Style::green`]).
+    fn green(&self) -> Styled<&Self> {
+        self.style().green()
+    }
+
+    /// Wraps the value in yellow foreground (see [`Style::yellow`]).
+    fn yellow(&self) -> Styled<&Self> {
+        self.style().yellow()
+    }
+
+    /// Wraps the value in blue foreground (see [`Style::blue`]).
+    fn blue(&self) -> Styled<&Self> {
+        self.style().blue()
+    }
+}
+
+impl<T: Display + ?Sized> Colors for T {}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output style_tests
+
+#[cfg(test)]
+mod style_tests {
+    use super::*;
+
+    #[test]
+    fn color_codes() {
+        let cases = [
+            (Style::new().fg(Color::Black), "\x1b[30m"),
+            (Style::new().fg(Color::White), "\x1b[37m"),
+            (Style::new().fg(Color::BrightBlack), "\x1b[90m"),
+            (Style::new().on(Color::BrightWhite), "\x1b[107m"),
+            (Style::new().on_red(), "\x1b[41m"),
+            (Style::new().fg(Color::Ansi256(208)), "\x1b[38;5;208m"),
+            (Style::new().on(Color::Rgb(1, 2, 3)), "\x1b[48;2;1;2;3m"),
+            (
+                Style::new().strikethrough().dim().italic().reverse().cyan(),
+                "\x1b[2;3;7;9;36m",
+            ),
+        ];
+
+        for (style, prefix) in cases {
+            let text = style.paint("x").enabled(true).to_string();
+            assert_eq!(text, format!("{prefix}x{RESET}"));
+        }
+
+        // A plain style writes no escape codes.
+        assert_eq!(Style::new().paint("x").enabled(true).to_string(), "x");
+    }
+
+    #[test]
+    fn detection_order() {
+        let set = |s: &str| Some(OsString::from(s));
+
+        assert!(detect_colors(None, None, true));
+        assert!(!detect_colors(None, None, false));
+        assert!(!detect_colors(set("1"), set("1"), true));
+        assert!(detect_colors(set(""), None, true));
+        assert!(detect_colors(None, set("1"), false));
+        assert!(!detect_colors(None, set("0"), false));
+    }
+
+    #[test]
+    fn stream_setting_overrides_global() {
+        assert_eq!(effective_setting(ON, OFF), ON);
+        assert_eq!(effective_setting(OFF, ON), OFF);
+        assert_eq!(effective_setting(AUTO, OFF), OFF);
+        assert_eq!(effective_setting(AUTO, AUTO), AUTO);
+    }
+
+    #[test]
+    fn stream_setting() {
+        // Stderr only: other tests do not depend on it.
+        set_stream_colors_enabled(Stream::Stderr, Some(true));
+
+        let styled = "x".red().for_stream(Stream::Stderr);
+        assert_eq!(styled.to_string(), "\x1b[31mx\x1b[0m");
+
+        set_stream_colors_enabled(Stream::Stderr, Some(false));
+        assert_eq!(styled.to_string(), "x");
+
+        set_stream_colors_enabled(Stream::Stderr, None);
+    }
+}
@@ -0,0 +1,726 @@
+//! # Tables
+//!
+//! A table builder for report output: headers, per-column alignment,
+//! automatic widths, borders, totals and highlighting of negative values.
+//!
+//! Numbers are formatted with [`thousands_separator`].
+
+use crate::{
+    FormatStyle, Stream, Style, add_sep, colors_enabled, display_width, thousands_separator,
+};
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Write as _},
+    io,
+};
+
+#[cfg(feature = "decimal")]
+use rust_decimal::{
+    Decimal,
+    prelude::{FromPrimitive, ToPrimitive},
+};
+
+/// A table cell.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Cell {
+    #[default]
+    Empty,
+    Text(String),
+    Integer(i128),
+    Number(f64),
+    #[cfg(feature = "decimal")]
+    Decimal(Decimal),
+}
+
+impl Cell {
+    /// Returns true for numeric cells.
+    pub fn is_numeric(&self) -> bool {
+        match self {
+            Cell::Integer(_) | Cell::Number(_) => true,
+            #[cfg(feature = "decimal")]
+            Cell::Decimal(_) => true,
+            Cell::Empty | Cell::Text(_) => false,
+        }
+    }
+
+    /// Returns true for negative numbers.
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Cell::Integer(n) => *n < 0,
+            Cell::Number(n) => *n < 0.0,
+            #[cfg(feature = "decimal")]
+            Cell::Decimal(n) => n.is_sign_negative() && !n.is_zero(),
+            Cell::Empty | Cell::Text(_) => false,
+        }
+    }
+
+    /**
+    Formats the cell; numbers use `decimals` places and the separators of `style`.
+
+    Example:
+    ```
+        use claudiofsr_lib::{Cell, FormatStyle};
+
+        assert_eq!(Cell::from(-1234.5).format(2, FormatStyle::PtBr), "-1.234,50");
+        assert_eq!(Cell::from(1234567).format(2, FormatStyle::Us), "1,234,567");
+        assert_eq!(Cell::from("CST 01").format(2, FormatStyle::Us), "CST 01");
+    ```
+    */
+    pub fn format(&self, decimals: usize, style: FormatStyle) -> Cow<'_, str> {
+        match self {
+            Cell::Empty => Cow::Borrowed(""),
+            Cell::Text(text) => Cow::Borrowed(text),
+            Cell::Integer(n) => Cow::Owned(format_integer(*n, style)),
+            Cell::Number(n) => Cow::Owned(thousands_separator(n, decimals, style)),
+            #[cfg(feature = "decimal")]
+            Cell::Decimal(n) => Cow::Owned(thousands_separator(n, decimals, style)),
+        }
+    }
+
+    /// Adds a numeric cell to a total (other cells are ignored).
+    ///
+    /// With decimals, the total stays a `Number` if a value is not a valid
+    /// `Decimal` (NaN, infinite or out of range) or the sum overflows.
+    fn add(self, other: &Cell) -> Cell {
+        match (self, other) {
+            (total, Cell::Empty | Cell::Text(_)) => total,
+            (Cell::Empty | Cell::Text(_), value) => value.clone(),
+            (Cell::Integer(a), Cell::Integer(b)) => Cell::Integer(a.saturating_add(*b)),
+            (Cell::Integer(a), Cell::Number(b)) => Cell::Number(a as f64 + b),
+            (Cell::Number(a), Cell::Integer(b)) => Cell::Number(a + *b as f64),
+            (Cell::Number(a), Cell::Number(b)) => Cell::Number(a + b),
+            #[cfg(feature = "decimal")]
+            (total, value) => match (to_decimal(&total), to_decimal(value)) {
+                (Some(a), Some(b)) if let Some(sum) = a.checked_add(b) => Cell::Decimal(sum),
+                _ => Cell::Number(to_f64(&total) + to_f64(value)),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "decimal")]
+fn to_decimal(cell: &Cell) -> Option<Decimal> {
+    match cell {
+        Cell::Integer(n) => Decimal::from_i128(*n),
+        Cell::Number(n) => Decimal::from_f64(*n),
+        Cell::Decimal(n) => Some(*n),
+        Cell::Empty | Cell::Text(_) => Some(Decimal::ZERO),
+    }
+}
+
+#[cfg(feature = "decimal")]
+fn to_f64(cell: &Cell) -> f64 {
+    match cell {
+        Cell::Integer(n) => *n as f64,
+        Cell::Number(n) => *n,
+        Cell::Decimal(n) => n.to_f64().unwrap_or(f64::NAN),
+        Cell::Empty | Cell::Text(_) => 0.0,
+    }
+}
+
+/// Formats an integer with the thousands separator of `style`.
+fn format_integer(n: i128, style: FormatStyle) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let mut result = String::with_capacity(digits.len() * 4 / 3 + 1);
+    if n < 0 {
+        result.push('-');
+    }
+    match style.separators().0 {
+        Some(separator) => add_sep(&digits, separator, &mut result),
+        None => result.push_str(&digits),
+    }
+    result
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Cell::Text(text.to_string())
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell::Text(text)
+    }
+}
+
+impl From<&String> for Cell {
+    fn from(text: &String) -> Self {
+        Cell::Text(text.clone())
+    }
+}
+
+impl From<f64> for Cell {
+    fn from(n: f64) -> Self {
+        Cell::Number(n)
+    }
+}
+
+impl From<f32> for Cell {
+    fn from(n: f32) -> Self {
+        Cell::Number(n.into())
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl From<Decimal> for Cell {
+    fn from(n: Decimal) -> Self {
+        Cell::Decimal(n)
+    }
+}
+
+impl<T: Into<Cell>> From<Option<T>> for Cell {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Cell::Empty, Into::into)
+    }
+}
+
+macro_rules! impl_cell_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Cell {
+                fn from(n: $t) -> Self {
+                    Cell::Integer(n as i128)
+                }
+            }
+        )*
+    };
+}
+
+impl_cell_from_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// Horizontal alignment of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// Table borders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Border {
+    /// Box-drawing characters: `┌─┬─┐`.
+    #[default]
+    Unicode,
+    /// ASCII characters: `+-+-+`.
+    Ascii,
+    /// No borders: columns separated by two spaces.
+    None,
+}
+
+/// Characters of a horizontal line: left, fill, junction and right.
+type Line = [char; 4];
+
+impl Border {
+    /// Returns the top, middle and bottom lines and the vertical bar.
+    fn chars(self) -> Option<(Line, Line, Line, char)> {
+        match self {
+            Border::Unicode => Some((
+                ['┌', '─', '┬', '┐'],
+                ['├', '─', '┼', '┤'],
+                ['└', '─', '┴', '┘'],
+                '│',
+            )),
+            Border::Ascii => Some((
+                ['+', '-', '+', '+'],
+                ['+', '-', '+', '+'],
+                ['+', '-', '+', '+'],
+                '|',
+            )),
+            Border::None => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Column {
+    header: String,
+    align: Option<Align>,
+    decimals: Option<usize>,
+    /// Leaves the column out of the totals row (codes, not amounts).
+    no_total: bool,
+}
+
+/**
+A table for terminal reports.
+
+Numeric columns are right-aligned and text columns left-aligned, unless
+set with [`align`](Table::align). Widths are computed from the content
+(see [`display_width`]).
+
+Example:
+```
+    use claudiofsr_lib::{Border, Cell, FormatStyle, Table};
+
+    let mut table = Table::new(["CST", "NatBcCred", "Valor"])
+        .format_style(FormatStyle::PtBr)
+        .border(Border::Ascii)
+        .totals("Total")
+        .no_total(1);
+
+    table.add_row([Cell::from("01"), Cell::from(1), Cell::from(1234.5)]);
+    table.add_row([Cell::from("50"), Cell::from(13), Cell::from(-34.25)]);
+
+    let expected = "\
++-------+-----------+----------+
+| CST   | NatBcCred |    Valor |
++-------+-----------+----------+
+| 01    |         1 | 1.234,50 |
+| 50    |        13 |   -34,25 |
++-------+-----------+----------+
+| Total |           | 1.200,25 |
++-------+-----------+----------+
+";
+    assert_eq!(table.to_string(), expected);
+```
+*/
+#[derive(Debug, Clone)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Cell>>,
+    totals: Option<String>,
+    format_style: FormatStyle,
+    decimals: usize,
+    border: Border,
+    negative_style: Option<Style>,
+    colors: Option<bool>,
+}
+
+impl Table {
+    /// Creates a table with the given headers.
+    pub fn new<I, S>(headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let columns = headers
+            .into_iter()
+            .map(|header| Column {
+                header: header.into(),
+                ..Column::default()
+            })
+            .collect();
+
+        Table {
+            columns,
+            rows: Vec::new(),
+            totals: None,
+            format_style: FormatStyle::PtBr,
+            decimals: 2,
+            border: Border::default(),
+            negative_style: None,
+            colors: None,
+        }
+    }
+
+    /// Sets the alignment of a column (starting at 0).
+    pub fn align(mut self, column: usize, align: Align) -> Self {
+        self.column_mut(column).align = Some(align);
+        self
+    }
+
+    /// Sets the number of decimal places of every numeric column (2 by default).
+    pub fn decimals(mut self, decimals: usize) -> Self {
+        self.decimals = decimals;
+        self
+    }
+
+    /// Sets the number of decimal places of a column.
+    pub fn column_decimals(mut self, column: usize, decimals: usize) -> Self {
+        self.column_mut(column).decimals = Some(decimals);
+        self
+    }
+
+    /// Sets the thousands and decimal separators (`PtBr` by default).
+    pub fn format_style(mut self, format_style: FormatStyle) -> Self {
+        self.format_style = format_style;
+        self
+    }
+
+    /// Sets the borders.
+    pub fn border(mut self, border: Border) -> Self {
+        self.border = border;
+        self
+    }
+
+    /// Adds a totals row, with `label` in the first column and the sum of each numeric column
+    /// (see [`no_total`](Table::no_total)).
+    pub fn totals(mut self, label: impl Into<String>) -> Self {
+        self.totals = Some(label.into());
+        self
+    }
+
+    /// Leaves a column out of the totals row, for numeric codes
+    /// (such as CST or NatBcCred) that must not be summed.
+    pub fn no_total(mut self, column: usize) -> Self {
+        self.column_mut(column).no_total = true;
+        self
+    }
+
+    /// Writes negative numbers in red (see [`Colors`](crate::Colors)).
+    pub fn highlight_negatives(mut self, highlight: bool) -> Self {
+        self.negative_style = highlight.then(|| Style::new().red());
+        self
+    }
+
+    /// Writes colors or not, ignoring the color settings of stdout
+    /// (see [`colors_enabled`]).
+    pub fn colors(mut self, enabled: bool) -> Self {
+        self.colors = Some(enabled);
+        self
+    }
+
+    /// Adds a row. Missing cells are empty; extra cells add columns without header.
+    pub fn add_row<I, C>(&mut self, row: I)
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<Cell>,
+    {
+        let row: Vec<Cell> = row.into_iter().map(Into::into).collect();
+        if row.len() > self.columns.len() {
+            self.columns.resize_with(row.len(), Column::default);
+        }
+        self.rows.push(row);
+    }
+
+    /// Returns the number of rows (without headers and totals).
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns true if the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Writes the table to `writer`.
+    pub fn write_to<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "{self}")
+    }
+
+    fn column_mut(&mut self, column: usize) -> &mut Column {
+        if column >= self.columns.len() {
+            self.columns.resize_with(column + 1, Column::default);
+        }
+        &mut self.columns[column]
+    }
+
+    /// Returns the headers.
+    pub(crate) fn headers(&self) -> impl Iterator<Item = &str> {
+        self.columns.iter().map(|column| column.header.as_str())
+    }
+
+    /// Returns the rows (without totals).
+    pub(crate) fn rows(&self) -> &[Vec<Cell>] {
+        &self.rows
+    }
+
+    /// Returns true if negative numbers are highlighted.
+    pub(crate) fn highlights_negatives(&self) -> bool {
+        self.negative_style.is_some()
+    }
+
+    fn cell(row: &[Cell], column: usize) -> &Cell {
+        row.get(column).unwrap_or(&Cell::Empty)
+    }
+
+    /// Returns the totals row: the label, then the sum of each numeric column
+    /// (except those set with [`no_total`](Table::no_total)).
+    pub(crate) fn totals_row(&self) -> Option<Vec<Cell>> {
+        let label = self.totals.as_ref()?;
+        let mut totals: Vec<Cell> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                if column.no_total {
+                    return Cell::Empty;
+                }
+                self.rows
+                    .iter()
+                    .fold(Cell::Empty, |total, row| total.add(Self::cell(row, index)))
+            })
+            .collect();
+
+        if let Some(first) = totals.first_mut() {
+            *first = Cell::Text(label.clone());
+        }
+        Some(totals)
+    }
+
+    /// Returns the alignment of each column: the one set, or right for numbers.
+    pub(crate) fn alignments(&self) -> Vec<Align> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                column.align.unwrap_or_else(|| {
+                    let mut cells = self
+                        .rows
+                        .iter()
+                        .map(|row| Self::cell(row, index))
+                        .filter(|cell| **cell != Cell::Empty)
+                        .peekable();
+                    let numeric = cells.peek().is_some() && cells.all(Cell::is_numeric);
+                    if numeric { Align::Right } else { Align::Left }
+                })
+            })
+            .collect()
+    }
+
+    /// Formats every cell of a row.
+    pub(crate) fn format_row<'a>(&self, row: &'a [Cell], style: FormatStyle) -> Vec<Cow<'a, str>> {
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                let decimals = column.decimals.unwrap_or(self.decimals);
+                Self::cell(row, index).format(decimals, style)
+            })
+            .collect()
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let aligns = self.alignments();
+        let totals = self.totals_row();
+        let headers: Vec<Cow<str>> = self
+            .columns
+            .iter()
+            .map(|column| Cow::Borrowed(column.header.as_str()))
+            .collect();
+
+        let body: Vec<(&[Cell], Vec<Cow<str>>)> = self
+            .rows
+            .iter()
+            .chain(&totals)
+            .map(|row| (row.as_slice(), self.format_row(row, self.format_style)))
+            .collect();
+
+        let widths: Vec<usize> = (0..self.columns.len())
+            .map(|column| {
+                body.iter()
+                    .map(|(_, texts)| &texts[column])
+                    .chain([&headers[column]])
+                    .map(|text| display_width(text))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let negative_style = self.negative_style.filter(|_| {
+            self.colors
+                .unwrap_or_else(|| colors_enabled(Stream::Stdout))
+        });
+
+        let writer = RowWriter {
+            border: self.border,
+            aligns: &aligns,
+            widths: &widths,
+        };
+
+        writer.line(f, |(top, _, _, _)| top)?;
+        writer.row(f, &headers, &[], None)?;
+        writer.line(f, |(_, middle, _, _)| middle)?;
+
+        for (index, (cells, texts)) in body.iter().enumerate() {
+            if totals.is_some() && index == self.rows.len() {
+                writer.line(f, |(_, middle, _, _)| middle)?;
+            }
+            writer.row(f, texts, cells, negative_style)?;
+        }
+
+        writer.line(f, |(_, _, bottom, _)| bottom)
+    }
+}
+
+/// Writes the lines of a table with computed widths.
+struct RowWriter<'a> {
+    border: Border,
+    aligns: &'a [Align],
+    widths: &'a [usize],
+}
+
+impl RowWriter<'_> {
+    /// Writes a horizontal line (nothing without borders).
+    fn line<F>(&self, f: &mut fmt::Formatter, select: F) -> fmt::Result
+    where
+        F: Fn((Line, Line, Line, char)) -> Line,
+    {
+        let Some(chars) = self.border.chars() else {
+            return Ok(());
+        };
+        let [left, fill, junction, right] = select(chars);
+
+        f.write_char(left)?;
+        for (index, width) in self.widths.iter().enumerate() {
+            if index > 0 {
+                f.write_char(junction)?;
+            }
+            for _ in 0..width + 2 {
+                f.write_char(fill)?;
+            }
+        }
+        f.write_char(right)?;
+        f.write_char('\n')
+    }
+
+    /// Writes a row of texts; negative numbers of `cells` use `negative_style`.
+    fn row(
+        &self,
+        f: &mut fmt::Formatter,
+        texts: &[Cow<str>],
+        cells: &[Cell],
+        negative_style: Option<Style>,
+    ) -> fmt::Result {
+        let vertical = self.border.chars().map(|(_, _, _, vertical)| vertical);
+        let mut line = String::new();
+
+        for (index, text) in texts.iter().enumerate() {
+            match vertical {
+                Some(vertical) if index == 0 => {
+                    line.push(vertical);
+                    line.push(' ');
+                }
+                Some(vertical) => {
+                    line.push(' ');
+                    line.push(vertical);
+                    line.push(' ');
+                }
+                None if index > 0 => line.push_str("  "),
+                None => {}
+            }
+
+            let padding = self.widths[index].saturating_sub(display_width(text));
+            let (before, after) = match self.aligns[index] {
+                Align::Left => (0, padding),
+                Align::Right => (padding, 0),
+                Align::Center => (padding / 2, padding - padding / 2),
+            };
+
+            let negative = cells.get(index).is_some_and(Cell::is_negative);
+            line.extend(std::iter::repeat_n(' ', before));
+            match negative_style {
+                Some(style) if negative => write!(line, "{}", style.paint(text).enabled(true))?,
+                _ => line.push_str(text),
+            }
+            line.extend(std::iter::repeat_n(' ', after));
+        }
+
+        match vertical {
+            Some(vertical) => {
+                line.push(' ');
+                line.push(vertical);
+            }
+            None => line.truncate(line.trim_end().len()),
+        }
+
+        f.write_str(&line)?;
+        f.write_char('\n')
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output table_tests
+// cargo test --features decimal -- --show-output table_tests
+
+#[cfg(test)]
+mod table_tests {
+    use super::*;
+
+    #[test]
+    fn unicode_borders_and_wide_text() {
+        let mut table = Table::new(["Nome", "Qtd"]).align(0, Align::Center);
+        table.add_row([Cell::from("おはよう"), Cell::from(1500)]);
+        table.add_row([Cell::from("ab"), Cell::from(Some(2))]);
+
+        let expected = "\
+┌──────────┬───────┐
+│   Nome   │   Qtd │
+├──────────┼───────┤
+│ おはよう │ 1.500 │
+│    ab    │     2 │
+└──────────┴───────┘
+";
+        assert_eq!(table.to_string(), expected);
+    }
+
+    #[test]
+    fn no_borders_and_missing_cells() {
+        let mut table = Table::new(["A"]).border(Border::None).decimals(1);
+        table.add_row([Cell::from("x"), Cell::from(1.25), Cell::from("extra")]);
+        table.add_row([Cell::from("long text")]);
+
+        let expected = "\
+A
+x          1,2  extra
+long text
+";
+        assert_eq!(table.to_string(), expected);
+        assert_eq!(table.len(), 2);
+    }
+
+    #[test]
+    fn negatives_in_red_keep_alignment() -> io::Result<()> {
+        let mut table = Table::new(["Valor"])
+            .border(Border::Ascii)
+            .format_style(FormatStyle::Us)
+            .highlight_negatives(true)
+            .colors(true);
+        table.add_row([-5.0]);
+        table.add_row([1000.0]);
+
+        let mut output = Vec::new();
+        table.write_to(&mut output)?;
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("|    \x1b[31m-5.00\x1b[0m |"));
+        assert!(output.contains("| 1,000.00 |"));
+        Ok(())
+    }
+
+    #[test]
+    fn totals_skip_code_columns() {
+        let mut table = Table::new(["Conta", "CST", "Valor"])
+            .border(Border::None)
+            .totals("Total")
+            .no_total(1);
+        table.add_row([Cell::from("a"), Cell::from(1), Cell::from(10)]);
+        table.add_row([Cell::from("b"), Cell::from(50), Cell::from(5)]);
+
+        let totals = table.totals_row().unwrap();
+        assert_eq!(totals[1], Cell::Empty);
+        assert_eq!(totals[2], Cell::Integer(15));
+    }
+
+    #[test]
+    fn nan_is_kept_in_totals() {
+        let mut table = Table::new(["Conta", "Valor"])
+            .border(Border::None)
+            .totals("Total");
+        table.add_row([Cell::from("a"), Cell::from(10)]);
+        table.add_row([Cell::from("b"), Cell::from(f64::NAN)]);
+        #[cfg(feature = "decimal")]
+        table.add_row([Cell::from("c"), Cell::from(Decimal::ONE)]);
+
+        let totals = table.totals_row().unwrap();
+        assert!(matches!(totals[1], Cell::Number(n) if n.is_nan()));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn decimal_totals() {
+        let mut table = Table::new(["Conta", "Valor"])
+            .border(Border::None)
+            .totals("Total");
+        table.add_row([Cell::from("a"), Cell::from(Decimal::new(10005, 3))]);
+        table.add_row([Cell::from("b"), Cell::from(2)]);
+
+        assert!(table.to_string().ends_with("Total  12,00\n"));
+    }
+}
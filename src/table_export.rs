@@ -0,0 +1,388 @@
+//! # Table Export
+//!
+//! Renders a [`Table`] as Markdown, CSV or HTML, with the numbers
+//! formatted by the [`FormatStyle`] of each output.
+//!
+//! Each renderer is a `Display` wrapper: use `to_string()`, or
+//! `write!` it to a file.
+
+use crate::{Align, Cell, FormatStyle, Table};
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Write as _},
+};
+
+impl Table {
+    /**
+    Renders the table as a Markdown (GitHub Flavored) table.
+
+    The totals row is written in bold. The characters `|`, `\`, `*`, `_`
+    and `` ` `` in the cells are escaped with a backslash.
+
+    Example:
+    ```
+        use claudiofsr_lib::{Cell, FormatStyle, Table};
+
+        let mut table = Table::new(["CST", "Valor"]).totals("Total");
+        table.add_row([Cell::from("01|50"), Cell::from(1234.5)]);
+
+        let expected = "\
+    | CST | Valor |
+    | :-- | --: |
+    | 01\\|50 | 1,234.50 |
+    | **Total** | **1,234.50** |
+    ";
+        assert_eq!(table.markdown(FormatStyle::Us).to_string(), expected);
+    ```
+    */
+    pub fn markdown(&self, style: FormatStyle) -> Markdown<'_> {
+        Markdown { table: self, style }
+    }
+
+    /**
+    Renders the table as CSV ([RFC 4180](https://www.rfc-editor.org/rfc/rfc4180)).
+
+    The delimiter is `;` if the decimal separator of `style` is a comma
+    (as expected by Excel in pt-BR), and `,` otherwise.
+    `FormatStyle::Sped` writes numbers without thousands separators.
+
+    Example:
+    ```
+        use claudiofsr_lib::{Cell, FormatStyle, Table};
+
+        let mut table = Table::new(["Descrição", "Valor"]);
+        table.add_row([Cell::from("Venda; \"à vista\""), Cell::from(-1234.5)]);
+
+        let csv = table.csv(FormatStyle::Sped).to_string();
+        assert_eq!(csv, "Descrição;Valor\r\n\"Venda; \"\"à vista\"\"\";-1234,50\r\n");
+
+        let csv = table.csv(FormatStyle::Us).to_string();
+        assert_eq!(csv, "Descrição,Valor\r\n\"Venda; \"\"à vista\"\"\",\"-1,234.50\"\r\n");
+    ```
+    */
+    pub fn csv(&self, style: FormatStyle) -> Csv<'_> {
+        let delimiter = match style.separators() {
+            (_, ",") => ';',
+            _ => ',',
+        };
+        Csv {
+            table: self,
+            style,
+            delimiter,
+        }
+    }
+
+    /**
+    Renders the table as an HTML `<table>`, for e-mails and web pages.
+
+    Text is escaped. Numeric columns are right-aligned, the totals row goes
+    in `<tfoot>`, and negative numbers are red if highlighted
+    (see [`Table::highlight_negatives`]).
+
+    Example:
+    ```
+        use claudiofsr_lib::{Cell, FormatStyle, Table};
+
+        let mut table = Table::new(["Conta", "Saldo"]).highlight_negatives(true);
+        table.add_row([Cell::from("<Caixa>"), Cell::from(-10.0)]);
+
+        let html = table.html(FormatStyle::PtBr).to_string();
+        assert!(html.contains("<td>&lt;Caixa&gt;</td>"));
+        assert!(html.contains(r#"<td style="text-align: right; color: red">-10,00</td>"#));
+    ```
+    */
+    pub fn html(&self, style: FormatStyle) -> Html<'_> {
+        Html { table: self, style }
+    }
+}
+
+/// Markdown renderer of a [`Table`] (see [`Table::markdown`]).
+#[derive(Debug, Clone, Copy)]
+pub struct Markdown<'a> {
+    table: &'a Table,
+    style: FormatStyle,
+}
+
+impl Markdown<'_> {
+    fn write_row<'a, I>(f: &mut fmt::Formatter, texts: I, bold: bool) -> fmt::Result
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        f.write_char('|')?;
+        for text in texts {
+            f.write_char(' ')?;
+            let emphasis = if bold && !text.is_empty() { "**" } else { "" };
+            f.write_str(emphasis)?;
+            for c in text.chars() {
+                match c {
+                    // Table separator and emphasis characters.
+                    '|' | '\\' | '*' | '_' | '`' => {
+                        f.write_char('\\')?;
+                        f.write_char(c)?;
+                    }
+                    '\n' => f.write_str("<br>")?,
+                    '\r' => {}
+                    c => f.write_char(c)?,
+                }
+            }
+            f.write_str(emphasis)?;
+            f.write_str(" |")?;
+        }
+        f.write_char('\n')
+    }
+}
+
+impl Display for Markdown<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = self.table;
+        Self::write_row(f, table.headers(), false)?;
+
+        f.write_char('|')?;
+        for align in table.alignments() {
+            f.write_str(match align {
+                Align::Left => " :-- |",
+                Align::Right => " --: |",
+                Align::Center => " :-: |",
+            })?;
+        }
+        f.write_char('\n')?;
+
+        for row in table.rows() {
+            let texts = table.format_row(row, self.style);
+            Self::write_row(f, texts.iter().map(|text| text.as_ref()), false)?;
+        }
+
+        if let Some(totals) = table.totals_row() {
+            let texts = table.format_row(&totals, self.style);
+            Self::write_row(f, texts.iter().map(|text| text.as_ref()), true)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// CSV renderer of a [`Table`] (see [`Table::csv`]).
+#[derive(Debug, Clone, Copy)]
+pub struct Csv<'a> {
+    table: &'a Table,
+    style: FormatStyle,
+    delimiter: char,
+}
+
+impl Csv<'_> {
+    /// Sets the delimiter.
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Writes a record; fields with the delimiter, quotes or line breaks are quoted.
+    fn write_record<'a, I>(&self, f: &mut fmt::Formatter, fields: I) -> fmt::Result
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        for (index, field) in fields.into_iter().enumerate() {
+            if index > 0 {
+                f.write_char(self.delimiter)?;
+            }
+
+            let quote = field.contains([self.delimiter, '"', '\r', '\n']);
+            if quote {
+                f.write_char('"')?;
+                f.write_str(&field.replace('"', "\"\""))?;
+                f.write_char('"')?;
+            } else {
+                f.write_str(field)?;
+            }
+        }
+        f.write_str("\r\n")
+    }
+}
+
+impl Display for Csv<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = self.table;
+        self.write_record(f, table.headers())?;
+
+        let totals = table.totals_row();
+        for row in table.rows().iter().chain(&totals) {
+            let texts = table.format_row(row, self.style);
+            self.write_record(f, texts.iter().map(|text| text.as_ref()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// HTML renderer of a [`Table`] (see [`Table::html`]).
+#[derive(Debug, Clone, Copy)]
+pub struct Html<'a> {
+    table: &'a Table,
+    style: FormatStyle,
+}
+
+impl Html<'_> {
+    fn write_row(&self, f: &mut fmt::Formatter, row: &[Cell], aligns: &[Align]) -> fmt::Result {
+        let texts = self.table.format_row(row, self.style);
+        let highlight = self.table.highlights_negatives();
+
+        f.write_str("    <tr>")?;
+        for (index, text) in texts.iter().enumerate() {
+            let mut styles = Vec::new();
+            match aligns[index] {
+                Align::Left => {}
+                Align::Right => styles.push("text-align: right"),
+                Align::Center => styles.push("text-align: center"),
+            }
+            if highlight && row.get(index).is_some_and(Cell::is_negative) {
+                styles.push("color: red");
+            }
+
+            if styles.is_empty() {
+                f.write_str("<td>")?;
+            } else {
+                write!(f, "<td style=\"{}\">", styles.join("; "))?;
+            }
+            f.write_str(&escape_html(text))?;
+            f.write_str("</td>")?;
+        }
+        f.write_str("</tr>\n")
+    }
+}
+
+impl Display for Html<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let table = self.table;
+        let aligns = table.alignments();
+
+        f.write_str("<table>\n  <thead>\n    <tr>")?;
+        for (header, align) in table.headers().zip(&aligns) {
+            match align {
+                Align::Left => f.write_str("<th>")?,
+                Align::Right => f.write_str("<th style=\"text-align: right\">")?,
+                Align::Center => f.write_str("<th style=\"text-align: center\">")?,
+            }
+            f.write_str(&escape_html(header))?;
+            f.write_str("</th>")?;
+        }
+        f.write_str("</tr>\n  </thead>\n  <tbody>\n")?;
+
+        for row in table.rows() {
+            self.write_row(f, row, &aligns)?;
+        }
+        f.write_str("  </tbody>\n")?;
+
+        if let Some(totals) = table.totals_row() {
+            f.write_str("  <tfoot>\n")?;
+            self.write_row(f, &totals, &aligns)?;
+            f.write_str("  </tfoot>\n")?;
+        }
+
+        f.write_str("</table>\n")
+    }
+}
+
+/// Escapes the HTML special characters of `text`.
+fn escape_html(text: &str) -> Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"', '\'']) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut escaped = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output table_export_tests
+
+#[cfg(test)]
+mod table_export_tests {
+    use super::*;
+
+    fn report() -> Table {
+        let mut table = Table::new(["CST", "Descrição", "Valor"]).totals("Total");
+        table.add_row([
+            Cell::from(1),
+            Cell::from("Linha 1\nLinha 2"),
+            Cell::from(1500.0),
+        ]);
+        table.add_row([Cell::from(50), Cell::from("A & B"), Cell::from(-0.5)]);
+        table
+    }
+
+    #[test]
+    fn csv_quoting_and_totals() {
+        let csv = report().csv(FormatStyle::PtBr).to_string();
+        let expected = "\
+CST;Descrição;Valor\r
+1;\"Linha 1\nLinha 2\";1.500,00\r
+50;A & B;-0,50\r
+Total;;1.499,50\r
+";
+        assert_eq!(csv, expected);
+
+        let csv = report().csv(FormatStyle::Us).delimiter('\t').to_string();
+        assert!(csv.ends_with("Total\t\t1,499.50\r\n"));
+    }
+
+    #[test]
+    fn markdown_escapes_and_alignment() {
+        let markdown = report().markdown(FormatStyle::Sped).to_string();
+        let expected = "\
+| CST | Descrição | Valor |
+| --: | :-- | --: |
+| 1 | Linha 1<br>Linha 2 | 1500,00 |
+| 50 | A & B | -0,50 |
+| **Total** |  | **1499,50** |
+";
+        assert_eq!(markdown, expected);
+    }
+
+    #[test]
+    fn markdown_escapes_emphasis() {
+        let mut table = Table::new(["Conta", "Valor"]).totals("Total *");
+        table.add_row([Cell::from("a_b `c` \\*"), Cell::from(1)]);
+
+        let markdown = table.markdown(FormatStyle::Us).to_string();
+        let expected = "\
+| Conta | Valor |
+| :-- | --: |
+| a\\_b \\`c\\` \\\\\\* | 1 |
+| **Total \\*** | **1** |
+";
+        assert_eq!(markdown, expected);
+    }
+
+    #[test]
+    fn html_structure() {
+        let html = report().html(FormatStyle::PtBr).to_string();
+        let expected = "\
+<table>
+  <thead>
+    <tr><th style=\"text-align: right\">CST</th><th>Descrição</th><th style=\"text-align: right\">Valor</th></tr>
+  </thead>
+  <tbody>
+    <tr><td style=\"text-align: right\">1</td><td>Linha 1\nLinha 2</td><td style=\"text-align: right\">1.500,00</td></tr>
+    <tr><td style=\"text-align: right\">50</td><td>A &amp; B</td><td style=\"text-align: right\">-0,50</td></tr>
+  </tbody>
+  <tfoot>
+    <tr><td style=\"text-align: right\">Total</td><td></td><td style=\"text-align: right\">1.499,50</td></tr>
+  </tfoot>
+</table>
+";
+        assert_eq!(html, expected);
+    }
+}
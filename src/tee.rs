@@ -0,0 +1,369 @@
+//! # Tee Writer
+//!
+//! Duplicates output to any number of sinks (files, stdout, in-memory buffers
+//! and, with the `gzip` feature, compressed files), like the unix `tee` command.
+
+use crate::AtomicFile;
+use std::{
+    io::{self, Write},
+    path::Path,
+    str,
+};
+
+#[cfg(feature = "gzip")]
+use flate2::{Compression, write::GzEncoder};
+
+/**
+A writer that converts its input to valid UTF-8, replacing invalid
+sequences with `U+FFFD` (like [`String::from_utf8_lossy`]).
+
+Multi-byte sequences split across calls to `write` are kept until complete,
+so the output is the same regardless of how the input is chunked.
+
+Example:
+```
+    use claudiofsr_lib::Utf8LossyWriter;
+    use std::io::Write;
+
+    let mut writer = Utf8LossyWriter::new(Vec::new());
+
+    let bytes = "ação".as_bytes();
+    writer.write_all(&bytes[..2]).unwrap(); // "a" + half of "ç"
+    writer.write_all(&bytes[2..]).unwrap();
+    writer.write_all(b" \xFF!").unwrap();
+
+    let output: Vec<u8> = writer.finish().unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), "ação \u{FFFD}!");
+```
+*/
+pub struct Utf8LossyWriter<W: Write> {
+    inner: W,
+    /// Incomplete UTF-8 sequence at the end of the last write.
+    pending: Vec<u8>,
+}
+
+impl<W: Write> Utf8LossyWriter<W> {
+    /// Creates a new lossy writer.
+    pub fn new(inner: W) -> Self {
+        Utf8LossyWriter {
+            inner,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Writes any incomplete sequence as `U+FFFD`, flushes and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            self.inner
+                .write_all(char::REPLACEMENT_CHARACTER.to_string().as_bytes())?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Writes the valid UTF-8 prefix of `bytes`, returning the incomplete tail.
+    fn write_lossy<'b>(&mut self, mut bytes: &'b [u8]) -> io::Result<&'b [u8]> {
+        loop {
+            match str::from_utf8(bytes) {
+                Ok(valid) => {
+                    self.inner.write_all(valid.as_bytes())?;
+                    return Ok(&[]);
+                }
+                Err(error) => {
+                    let (valid, invalid) = bytes.split_at(error.valid_up_to());
+                    self.inner.write_all(valid)?;
+
+                    match error.error_len() {
+                        Some(len) => {
+                            self.inner
+                                .write_all(char::REPLACEMENT_CHARACTER.to_string().as_bytes())?;
+                            bytes = &invalid[len..];
+                        }
+                        None => return Ok(invalid),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<W: Write> Write for Utf8LossyWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.pending.is_empty() {
+            let tail = self.write_lossy(buf)?;
+            self.pending.extend_from_slice(tail);
+        } else {
+            let mut bytes = std::mem::take(&mut self.pending);
+            bytes.extend_from_slice(buf);
+            let tail = self.write_lossy(&bytes)?;
+            self.pending.extend_from_slice(tail);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A destination of the [`Tee`] writer.
+enum Sink<'a> {
+    Writer(Box<dyn Write + 'a>),
+    Lossy(Utf8LossyWriter<Box<dyn Write + 'a>>),
+    Atomic(AtomicFile),
+    #[cfg(feature = "gzip")]
+    Gzip(GzEncoder<AtomicFile>),
+}
+
+impl Sink<'_> {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Sink::Writer(writer) => writer,
+            Sink::Lossy(writer) => writer,
+            Sink::Atomic(file) => file,
+            #[cfg(feature = "gzip")]
+            Sink::Gzip(encoder) => encoder,
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Sink::Writer(mut writer) => writer.flush(),
+            Sink::Lossy(writer) => writer.finish().map(drop),
+            Sink::Atomic(file) => file.commit(),
+            #[cfg(feature = "gzip")]
+            Sink::Gzip(encoder) => encoder.finish()?.commit(),
+        }
+    }
+}
+
+/**
+A writer that duplicates everything written to it into several sinks.
+
+Errors from any sink are propagated, prefixed with the index of the sink.
+
+Call [`Tee::finish`] at the end to flush every sink, complete
+compressed output and commit files: files are written atomically
+(see [`AtomicFile`]) and only replace their target on `finish`.
+
+Example:
+```
+    use claudiofsr_lib::Tee;
+    use std::io::Write;
+
+    let mut first: Vec<u8> = Vec::new();
+    let mut second: Vec<u8> = Vec::new();
+
+    let mut tee = Tee::new()
+        .with_writer(&mut first)
+        .with_lossy_writer(&mut second);
+
+    tee.write_all(b"Relat\xF3rio\n").unwrap();
+    tee.finish().unwrap();
+
+    assert_eq!(first, b"Relat\xF3rio\n");
+    assert_eq!(String::from_utf8(second).unwrap(), "Relat\u{FFFD}rio\n");
+```
+*/
+#[derive(Default)]
+pub struct Tee<'a> {
+    sinks: Vec<Sink<'a>>,
+}
+
+impl<'a> Tee<'a> {
+    /// Creates a `Tee` without sinks.
+    pub fn new() -> Self {
+        Tee { sinks: Vec::new() }
+    }
+
+    /// Adds a writer that receives the bytes unchanged.
+    pub fn with_writer<W: Write + 'a>(mut self, writer: W) -> Self {
+        self.sinks.push(Sink::Writer(Box::new(writer)));
+        self
+    }
+
+    /// Adds a writer that receives the output as lossy UTF-8 (see [`Utf8LossyWriter`]).
+    pub fn with_lossy_writer<W: Write + 'a>(mut self, writer: W) -> Self {
+        let writer: Box<dyn Write + 'a> = Box::new(writer);
+        self.sinks.push(Sink::Lossy(Utf8LossyWriter::new(writer)));
+        self
+    }
+
+    /// Adds a file, atomically created or replaced at `path` on [`Tee::finish`].
+    pub fn with_file<P: AsRef<Path>>(mut self, path: P) -> io::Result<Self> {
+        let file = AtomicFile::create(path)?;
+        self.sinks.push(Sink::Atomic(file));
+        Ok(self)
+    }
+
+    /// Adds a gzip compressed file, atomically created or replaced at `path` on [`Tee::finish`].
+    #[cfg(feature = "gzip")]
+    pub fn with_gzip<P: AsRef<Path>>(mut self, path: P) -> io::Result<Self> {
+        let file = AtomicFile::create(path)?;
+        let encoder = GzEncoder::new(file, Compression::default());
+        self.sinks.push(Sink::Gzip(encoder));
+        Ok(self)
+    }
+
+    /// Adds the standard output, as lossy UTF-8.
+    pub fn with_stdout(self) -> Self {
+        self.with_lossy_writer(io::stdout())
+    }
+
+    /// Adds the standard error, as lossy UTF-8.
+    pub fn with_stderr(self) -> Self {
+        self.with_lossy_writer(io::stderr())
+    }
+
+    /// Returns the number of sinks.
+    pub fn len(&self) -> usize {
+        self.sinks.len()
+    }
+
+    /// Returns true if there are no sinks.
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// Flushes and closes every sink.
+    pub fn finish(self) -> io::Result<()> {
+        self.sinks
+            .into_iter()
+            .enumerate()
+            .try_for_each(|(index, sink)| sink.finish().map_err(|error| context(index, error)))
+    }
+}
+
+impl Write for Tee<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for (index, sink) in self.sinks.iter_mut().enumerate() {
+            sink.writer()
+                .write_all(buf)
+                .map_err(|error| context(index, error))?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        for (index, sink) in self.sinks.iter_mut().enumerate() {
+            sink.writer()
+                .flush()
+                .map_err(|error| context(index, error))?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds the sink index to an error.
+fn context(index: usize, error: io::Error) -> io::Error {
+    io::Error::new(error.kind(), format!("tee sink {index}: {error}"))
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output tee_tests
+// cargo test --features gzip -- --show-output tee_tests
+
+#[cfg(test)]
+mod tee_tests {
+    use super::*;
+
+    /// A writer that always fails.
+    struct Broken;
+
+    impl Write for Broken {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn lossy_writer_byte_by_byte() -> io::Result<()> {
+        let input = "|C170|Ação 🦀|".as_bytes();
+        let mut writer = Utf8LossyWriter::new(Vec::new());
+
+        for byte in input {
+            writer.write_all(&[*byte])?;
+        }
+
+        assert_eq!(writer.finish()?, input);
+        Ok(())
+    }
+
+    #[test]
+    fn lossy_writer_incomplete_sequence_at_end() -> io::Result<()> {
+        let mut writer = Utf8LossyWriter::new(Vec::new());
+        writer.write_all(b"ok \xE2\x82")?;
+
+        let output = writer.finish()?;
+        assert_eq!(String::from_utf8_lossy(&output), "ok \u{FFFD}");
+        Ok(())
+    }
+
+    #[test]
+    fn duplicates_to_every_sink() -> io::Result<()> {
+        let path = "/tmp/tee_sink.txt";
+        // Left behind if a previous run failed after finish.
+        let _ = std::fs::remove_file(path);
+
+        let mut buffer_a: Vec<u8> = Vec::new();
+        let mut buffer_b: Vec<u8> = Vec::new();
+
+        let mut tee = Tee::new()
+            .with_file(path)?
+            .with_writer(&mut buffer_a)
+            .with_writer(&mut buffer_b);
+
+        assert_eq!(tee.len(), 3);
+
+        writeln!(tee, "line 1")?;
+        writeln!(tee, "line 2")?;
+
+        // The file is only visible after finish.
+        assert!(!std::path::Path::new(path).exists());
+        tee.finish()?;
+
+        assert_eq!(buffer_a, b"line 1\nline 2\n");
+        assert_eq!(buffer_a, buffer_b);
+        assert_eq!(std::fs::read(path)?, buffer_a);
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn propagates_sink_errors() {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut tee = Tee::new().with_writer(&mut buffer).with_writer(Broken);
+
+        let error = tee.write_all(b"data").unwrap_err();
+        println!("error: {error}");
+        assert_eq!(error.to_string(), "tee sink 1: disk full");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_sink() -> io::Result<()> {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let path = "/tmp/tee_sink.txt.gz";
+        let mut tee = Tee::new().with_gzip(path)?;
+        tee.write_all(b"compressed report\n")?;
+        tee.finish()?;
+
+        let mut text = String::new();
+        GzDecoder::new(std::fs::File::open(path)?).read_to_string(&mut text)?;
+        assert_eq!(text, "compressed report\n");
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+}
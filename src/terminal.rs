@@ -0,0 +1,293 @@
+//! # Terminal Control
+//!
+//! Clears the screen, moves and hides the cursor with ANSI escape
+//! sequences, without spawning processes, and queries the terminal size.
+//!
+//! [`Terminal`] writes to any `io::Write`, so the sequences can be
+//! written into a buffer and checked in tests.
+//!
+//! On Windows the sequences require a terminal with virtual terminal
+//! processing (Windows Terminal, or the console of Windows 10 and later
+//! with `ENABLE_VIRTUAL_TERMINAL_PROCESSING`). This module does not enable it:
+//! legacy consoles print the escape codes as text.
+
+use crate::Stream;
+use std::{
+    env,
+    io::{self, Write},
+};
+
+/**
+Writes terminal control sequences to `W` (stdout, stderr or a buffer).
+
+The sequences are written as they are called; call [`flush`](Write::flush)
+(or use a method that flushes) to apply them.
+
+Example:
+```
+    use claudiofsr_lib::Terminal;
+    use std::io::Write;
+
+    let mut terminal = Terminal::new(Vec::new());
+    terminal.hide_cursor().unwrap();
+    terminal.move_to(0, 4).unwrap();
+    write!(terminal, "Processing").unwrap();
+    terminal.clear_line().unwrap();
+    terminal.show_cursor().unwrap();
+
+    let output = terminal.into_inner();
+    assert_eq!(output, b"\x1b[?25l\x1b[1;5HProcessing\r\x1b[2K\x1b[?25h");
+```
+*/
+#[derive(Debug)]
+pub struct Terminal<W> {
+    writer: W,
+}
+
+impl Terminal<io::Stdout> {
+    /// Writes to stdout.
+    pub fn stdout() -> Self {
+        Terminal::new(io::stdout())
+    }
+}
+
+impl Terminal<io::Stderr> {
+    /// Writes to stderr.
+    pub fn stderr() -> Self {
+        Terminal::new(io::stderr())
+    }
+}
+
+impl<W: Write> Terminal<W> {
+    /// Writes to `writer`.
+    pub fn new(writer: W) -> Self {
+        Terminal { writer }
+    }
+
+    /// Returns a reference to the writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns the writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Clears the screen and the scrollback, and moves the cursor to the top left.
+    pub fn clear_screen(&mut self) -> io::Result<()> {
+        self.writer.write_all(b"\x1b[2J\x1b[3J\x1b[H")?;
+        self.writer.flush()
+    }
+
+    /// Resets the terminal to its initial state (like `tput reset`).
+    pub fn reset(&mut self) -> io::Result<()> {
+        self.writer.write_all(b"\x1bc")?;
+        self.writer.flush()
+    }
+
+    /// Clears the current line and moves the cursor to its start.
+    pub fn clear_line(&mut self) -> io::Result<()> {
+        self.writer.write_all(b"\r\x1b[2K")
+    }
+
+    /// Clears from the cursor to the end of the line.
+    pub fn clear_to_end_of_line(&mut self) -> io::Result<()> {
+        self.writer.write_all(b"\x1b[K")
+    }
+
+    /// Clears from the cursor to the end of the screen.
+    pub fn clear_to_end_of_screen(&mut self) -> io::Result<()> {
+        self.writer.write_all(b"\x1b[J")
+    }
+
+    /// Moves the cursor to `row` and `column` (starting at 0).
+    pub fn move_to(&mut self, row: u16, column: u16) -> io::Result<()> {
+        let (row, column) = (u32::from(row) + 1, u32::from(column) + 1);
+        write!(self.writer, "\x1b[{row};{column}H")
+    }
+
+    /// Moves the cursor up `n` rows.
+    pub fn move_up(&mut self, n: u16) -> io::Result<()> {
+        self.move_cursor(n, 'A')
+    }
+
+    /// Moves the cursor down `n` rows.
+    pub fn move_down(&mut self, n: u16) -> io::Result<()> {
+        self.move_cursor(n, 'B')
+    }
+
+    /// Moves the cursor right `n` columns.
+    pub fn move_right(&mut self, n: u16) -> io::Result<()> {
+        self.move_cursor(n, 'C')
+    }
+
+    /// Moves the cursor left `n` columns.
+    pub fn move_left(&mut self, n: u16) -> io::Result<()> {
+        self.move_cursor(n, 'D')
+    }
+
+    /// Hides the cursor.
+    pub fn hide_cursor(&mut self) -> io::Result<()> {
+        self.writer.write_all(b"\x1b[?25l")
+    }
+
+    /// Shows the cursor.
+    pub fn show_cursor(&mut self) -> io::Result<()> {
+        self.writer.write_all(b"\x1b[?25h")
+    }
+
+    /// Writes `ESC [ n direction` (nothing if `n` is 0, which would move 1).
+    fn move_cursor(&mut self, n: u16, direction: char) -> io::Result<()> {
+        if n == 0 {
+            return Ok(());
+        }
+        write!(self.writer, "\x1b[{n}{direction}")
+    }
+}
+
+impl<W: Write> Write for Terminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Terminal size in character cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TerminalSize {
+    pub columns: u16,
+    pub rows: u16,
+}
+
+/**
+Returns the size of the terminal.
+
+On Unix the size is queried with the `TIOCGWINSZ` ioctl on stdout, stderr
+and stdin (the first that is a terminal). Otherwise the `COLUMNS` and
+`LINES` environment variables are used, if both are set.
+
+Returns `None` if the size is unknown (e.g. output redirected to a file).
+*/
+pub fn terminal_size() -> Option<TerminalSize> {
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsRawFd;
+
+        let fds = [
+            io::stdout().as_raw_fd(),
+            io::stderr().as_raw_fd(),
+            io::stdin().as_raw_fd(),
+        ];
+        if let Some(size) = fds.into_iter().find_map(ioctl_size) {
+            return Some(size);
+        }
+    }
+
+    size_from_env(env::var("COLUMNS").ok(), env::var("LINES").ok())
+}
+
+/// Queries the window size of the terminal `fd`.
+#[cfg(unix)]
+fn ioctl_size(fd: std::os::fd::RawFd) -> Option<TerminalSize> {
+    // Safety: `winsize` is plain data, valid when zeroed.
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+
+    // Safety: TIOCGWINSZ only writes a `winsize` into `size`;
+    // it fails (without writing) if `fd` is not a terminal.
+    let result = unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) };
+
+    (result == 0 && size.ws_col > 0 && size.ws_row > 0).then_some(TerminalSize {
+        columns: size.ws_col,
+        rows: size.ws_row,
+    })
+}
+
+/// Parses the `COLUMNS` and `LINES` environment variables.
+fn size_from_env(columns: Option<String>, lines: Option<String>) -> Option<TerminalSize> {
+    let parse = |value: Option<String>| value?.trim().parse::<u16>().ok().filter(|&n| n > 0);
+    Some(TerminalSize {
+        columns: parse(columns)?,
+        rows: parse(lines)?,
+    })
+}
+
+/// Returns true if `stream` is a terminal (TTY).
+pub fn is_tty(stream: Stream) -> bool {
+    stream.is_terminal()
+}
+
+/// Clear (wipe) the terminal screen
+///
+/// Writes escape sequences to stdout (see [`Terminal::clear_screen`]),
+/// only if stdout is a terminal.
+///
+/// On Windows this requires a terminal with virtual terminal processing
+/// (Windows Terminal, or the console of Windows 10 and later):
+/// legacy consoles print the escape codes as text.
+pub fn clear_terminal_screen() {
+    if is_tty(Stream::Stdout) {
+        let _ = Terminal::stdout().clear_screen();
+    }
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output terminal_tests
+
+#[cfg(test)]
+mod terminal_tests {
+    use super::*;
+
+    #[test]
+    fn cursor_sequences() -> io::Result<()> {
+        let mut terminal = Terminal::new(Vec::new());
+        terminal.move_up(2)?;
+        terminal.move_down(0)?;
+        terminal.move_right(10)?;
+        terminal.move_left(1)?;
+        terminal.move_to(u16::MAX, 0)?;
+        terminal.clear_to_end_of_line()?;
+        terminal.clear_to_end_of_screen()?;
+        terminal.clear_screen()?;
+        terminal.reset()?;
+
+        let output = String::from_utf8(terminal.into_inner()).unwrap();
+        assert_eq!(
+            output,
+            "\x1b[2A\x1b[10C\x1b[1D\x1b[65536;1H\x1b[K\x1b[J\x1b[2J\x1b[3J\x1b[H\x1bc"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn size_from_environment() {
+        let set = |s: &str| Some(s.to_string());
+
+        assert_eq!(
+            size_from_env(set("120"), set(" 40 ")),
+            Some(TerminalSize {
+                columns: 120,
+                rows: 40
+            })
+        );
+        assert_eq!(size_from_env(set("120"), None), None);
+        assert_eq!(size_from_env(set("0"), set("40")), None);
+        assert_eq!(size_from_env(set("wide"), set("40")), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ioctl_on_a_file_fails() -> io::Result<()> {
+        use std::os::fd::AsRawFd;
+
+        let file = std::fs::File::open("Cargo.toml")?;
+        assert_eq!(ioctl_size(file.as_raw_fd()), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,313 @@
+//! # Collection Writers
+//!
+//! Writes any collection of `Display` items (one per line) or any map
+//! (`key<separator>value` per line) to a file.
+
+use crate::{AtomicFile, Result};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    fs::OpenOptions,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Options for [`WriteLines::write_lines`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Separator between key and value when writing maps (default: `"\t"`).
+    pub separator: String,
+    /// Optional first line, such as column names.
+    pub header: Option<String>,
+    /// Appends to an existing file instead of replacing it (default: `false`).
+    pub append: bool,
+    /// Sorts the lines by their text (default: `false`).
+    pub sorted: bool,
+    /// Creates (or truncates) the file for an empty collection (default: `true`).
+    pub create_if_empty: bool,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions {
+            separator: "\t".to_string(),
+            header: None,
+            append: false,
+            sorted: false,
+            create_if_empty: true,
+        }
+    }
+}
+
+impl WriteOptions {
+    /// Sets the key/value separator used for maps.
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Sets the header line.
+    pub fn header(mut self, header: impl Into<String>) -> Self {
+        self.header = Some(header.into());
+        self
+    }
+
+    /// Appends instead of replacing the file.
+    pub fn append(mut self, append: bool) -> Self {
+        self.append = append;
+        self
+    }
+
+    /// Sorts the output lines.
+    pub fn sorted(mut self, sorted: bool) -> Self {
+        self.sorted = sorted;
+        self
+    }
+
+    /// Creates the file even if the collection is empty.
+    pub fn create_if_empty(mut self, create_if_empty: bool) -> Self {
+        self.create_if_empty = create_if_empty;
+        self
+    }
+}
+
+/// Marker type: [`WriteLines`] for collections of `Display` items.
+pub struct DisplayItems;
+
+/// Marker type: [`WriteLines`] for maps with `Display` keys and values.
+pub struct MapEntries;
+
+/**
+Writes a collection to a file, one item per line.
+
+Implemented for every type whose reference is an `IntoIterator` of `Display` items
+(`Vec`, slices, `VecDeque`, `HashSet`, `BTreeSet`, ...), and for `HashMap` and
+`BTreeMap`, written as `key<separator>value`.
+
+The type parameter `M` is a marker that distinguishes the two implementations;
+it is always inferred.
+
+### Behavior
+- Without `append`, the file is replaced atomically (see [`AtomicFile`]).
+- With `append`, the header is only written if the file is new or empty.
+- `sorted` orders lines by their text, which gives a stable output for
+  hash-based collections (numbers are compared as text: `"10" < "9"`).
+- An empty collection does not touch the file if `create_if_empty` is `false`.
+
+Example:
+```
+    use claudiofsr_lib::{WriteLines, WriteOptions};
+    use std::collections::HashMap;
+
+    let path = "/tmp/write_lines_example.txt";
+
+    let cst: HashMap<u16, &str> = HashMap::from([(51, "insumos"), (50, "revenda")]);
+    let options = WriteOptions::default()
+        .separator(";")
+        .header("cst;descricao")
+        .sorted(true);
+
+    cst.write_lines(path, &options).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        "cst;descricao\n50;revenda\n51;insumos\n"
+    );
+
+    let more = vec!["56;mista"];
+    more.write_lines(path, &WriteOptions::default().append(true)).unwrap();
+    assert!(std::fs::read_to_string(path).unwrap().ends_with("51;insumos\n56;mista\n"));
+
+    std::fs::remove_file(path).unwrap();
+```
+*/
+pub trait WriteLines<M> {
+    /// Writes the collection to `path` according to `options`.
+    fn write_lines<P>(&self, path: P, options: &WriteOptions) -> Result<()>
+    where
+        P: AsRef<Path>;
+}
+
+impl<C> WriteLines<DisplayItems> for C
+where
+    C: ?Sized,
+    for<'a> &'a C: IntoIterator,
+    for<'a> <&'a C as IntoIterator>::Item: Display,
+{
+    fn write_lines<P>(&self, path: P, options: &WriteOptions) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        write_items(self, path.as_ref(), options, |writer, item| {
+            writeln!(writer, "{item}")
+        })
+    }
+}
+
+impl<K, V, S> WriteLines<MapEntries> for HashMap<K, V, S>
+where
+    K: Display,
+    V: Display,
+{
+    fn write_lines<P>(&self, path: P, options: &WriteOptions) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let separator = &options.separator;
+        write_items(self, path.as_ref(), options, |writer, (key, value)| {
+            writeln!(writer, "{key}{separator}{value}")
+        })
+    }
+}
+
+impl<K, V> WriteLines<MapEntries> for BTreeMap<K, V>
+where
+    K: Display,
+    V: Display,
+{
+    fn write_lines<P>(&self, path: P, options: &WriteOptions) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let separator = &options.separator;
+        write_items(self, path.as_ref(), options, |writer, (key, value)| {
+            writeln!(writer, "{key}{separator}{value}")
+        })
+    }
+}
+
+/// Writes each item with `write_line`, applying the options.
+fn write_items<I, F>(items: I, path: &Path, options: &WriteOptions, write_line: F) -> Result<()>
+where
+    I: IntoIterator,
+    F: Fn(&mut dyn Write, I::Item) -> io::Result<()>,
+{
+    let mut items = items.into_iter().peekable();
+
+    if items.peek().is_none() && !options.create_if_empty {
+        return Ok(());
+    }
+
+    let write_all = |writer: &mut dyn Write, with_header: bool| -> io::Result<()> {
+        if let (Some(header), true) = (&options.header, with_header) {
+            writeln!(writer, "{header}")?;
+        }
+
+        if options.sorted {
+            let mut lines: Vec<Vec<u8>> = Vec::new();
+            for item in items {
+                let mut line = Vec::new();
+                write_line(&mut line, item)?;
+                lines.push(line);
+            }
+            lines.sort_unstable();
+            lines.iter().try_for_each(|line| writer.write_all(line))
+        } else {
+            items.try_for_each(|item| write_line(writer, item))
+        }
+    };
+
+    if options.append {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let is_empty = file.metadata()?.len() == 0;
+        let mut writer = BufWriter::new(file);
+        write_all(&mut writer, is_empty)?;
+        writer
+            .into_inner()
+            .map_err(io::IntoInnerError::into_error)?
+            .sync_all()?;
+    } else {
+        let mut file = AtomicFile::create(path)?;
+        write_all(&mut file, true)?;
+        file.commit()?;
+    }
+
+    Ok(())
+}
+
+//----------------------------------------------------------------------------//
+//                                   Tests                                    //
+//----------------------------------------------------------------------------//
+//
+// cargo test -- --show-output write_lines_tests
+
+#[cfg(test)]
+mod write_lines_tests {
+    use super::*;
+    use std::{
+        collections::{BTreeSet, HashSet, VecDeque},
+        fs,
+    };
+
+    fn read(path: &str) -> String {
+        let text = fs::read_to_string(path).unwrap();
+        fs::remove_file(path).unwrap();
+        text
+    }
+
+    #[test]
+    fn sequences_and_sets() -> Result<()> {
+        let path = "/tmp/write_lines_vec.txt";
+
+        vec![3, 1, 2].write_lines(path, &WriteOptions::default())?;
+        assert_eq!(read(path), "3\n1\n2\n");
+
+        let slice: &[&str] = &["b", "a"];
+        slice.write_lines(path, &WriteOptions::default().header("letter"))?;
+        assert_eq!(read(path), "letter\nb\na\n");
+
+        VecDeque::from([1.5, 2.5]).write_lines(path, &WriteOptions::default())?;
+        assert_eq!(read(path), "1.5\n2.5\n");
+
+        let set: HashSet<&str> = HashSet::from(["zz", "aa", "mm"]);
+        set.write_lines(path, &WriteOptions::default().sorted(true))?;
+        assert_eq!(read(path), "aa\nmm\nzz\n");
+
+        BTreeSet::from([2, 1]).write_lines(path, &WriteOptions::default())?;
+        assert_eq!(read(path), "1\n2\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn maps_with_separator() -> Result<()> {
+        let path = "/tmp/write_lines_map.txt";
+
+        let map: HashMap<&str, u32> = HashMap::from([("b", 2), ("a", 1), ("c", 3)]);
+        let options = WriteOptions::default().separator(" = ").sorted(true);
+        map.write_lines(path, &options)?;
+        assert_eq!(read(path), "a = 1\nb = 2\nc = 3\n");
+
+        let map: BTreeMap<u16, &str> = BTreeMap::from([(2, "y"), (1, "x")]);
+        map.write_lines(path, &WriteOptions::default())?;
+        assert_eq!(read(path), "1\tx\n2\ty\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn append_writes_header_once() -> Result<()> {
+        let path = "/tmp/write_lines_append.txt";
+        let _ = fs::remove_file(path);
+
+        let options = WriteOptions::default().header("id").append(true);
+        vec![1, 2].write_lines(path, &options)?;
+        vec![3].write_lines(path, &options)?;
+
+        assert_eq!(read(path), "id\n1\n2\n3\n");
+        Ok(())
+    }
+
+    #[test]
+    fn empty_collections() -> Result<()> {
+        let path = "/tmp/write_lines_empty.txt";
+        let empty: Vec<u32> = Vec::new();
+
+        empty.write_lines(path, &WriteOptions::default().create_if_empty(false))?;
+        assert!(!Path::new(path).exists());
+
+        empty.write_lines(path, &WriteOptions::default())?;
+        assert_eq!(read(path), "");
+
+        Ok(())
+    }
+}